@@ -47,6 +47,40 @@ impl<N: ArrayLength<u8>> ByteArray<N> {
     }
 }
 
+impl<N: ArrayLength<u8>> ByteArray<N> {
+    /// Like `try_from_slice`, but left-pads `bytes` with zeros if they are too short.
+    /// This accepts big-endian integers from which leading zeros have been stripped.
+    pub fn try_from_slice_padded(bytes: impl AsRef<[u8]>) -> Result<Self, String> {
+        let bytes = bytes.as_ref();
+        let len = N::to_usize();
+        if bytes.len() > len {
+            return Err(format!(
+                "expected at most {} bytes but got {}",
+                len,
+                bytes.len()
+            ));
+        }
+        let mut arr = GenericArray::default();
+        arr[len - bytes.len()..].copy_from_slice(bytes);
+        Ok(ByteArray(arr))
+    }
+}
+
+/// Deserializes an optional `ByteArray` using `ByteArray::try_from_slice_padded`.
+pub(crate) fn deserialize_opt_padded<'de, D: Deserializer<'de>, N: ArrayLength<u8>>(
+    d: D,
+) -> Result<Option<ByteArray<N>>, D::Error> {
+    let bytes = Zeroizing::new(deserialize_base64(d)?);
+    ByteArray::try_from_slice_padded(&*bytes)
+        .map(Some)
+        .map_err(|_| {
+            de::Error::invalid_length(
+                bytes.len(),
+                &format!("at most {} base64-encoded bytes", N::to_usize()).as_str(),
+            )
+        })
+}
+
 impl<N: ArrayLength<u8>> Serialize for ByteArray<N> {
     fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
         serialize_base64(self.0.as_slice(), s)
@@ -78,7 +112,7 @@ mod tests {
     static BASE64_JSON: &str = "\"AQIDBAUGBw==\"";
 
     fn get_de() -> serde_json::Deserializer<serde_json::de::StrRead<'static>> {
-        serde_json::Deserializer::from_str(&BASE64_JSON)
+        serde_json::Deserializer::from_str(BASE64_JSON)
     }
 
     #[test]
@@ -105,4 +139,17 @@ mod tests {
     fn test_serde_base64_deserialize_array_short() {
         ByteArray::<U8>::deserialize(&mut get_de()).unwrap_err();
     }
+
+    #[test]
+    fn test_serde_base64_deserialize_array_padded() {
+        let arr = deserialize_opt_padded::<_, U8>(&mut get_de())
+            .unwrap()
+            .unwrap();
+        assert_eq!(arr.0.as_slice(), &[0, 1, 2, 3, 4, 5, 6, 7]);
+        let arr = deserialize_opt_padded::<_, U7>(&mut get_de())
+            .unwrap()
+            .unwrap();
+        assert_eq!(arr.0.as_slice(), BYTES);
+        deserialize_opt_padded::<_, U6>(&mut get_de()).unwrap_err();
+    }
 }
@@ -0,0 +1,322 @@
+use derive_more::{AsRef, Deref, From};
+use serde::{
+    de::{self, Deserialize, Deserializer},
+    ser::{Serialize, Serializer},
+};
+
+/// A DER-encoded X.509 certificate, as found in the `x5c` member. Unlike other binary members,
+/// certificates are encoded using standard (not URL-safe) base64, as per
+/// [RFC 7517 §4.7](https://tools.ietf.org/html/rfc7517#section-4.7).
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Deref, AsRef, From)]
+pub struct Base64DerCertificate(pub Vec<u8>);
+
+impl Serialize for Base64DerCertificate {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        base64::encode_config(&self.0, base64::STANDARD).serialize(s)
+    }
+}
+
+impl<'de> Deserialize<'de> for Base64DerCertificate {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let b64 = String::deserialize(d)?;
+        let der = base64::decode_config(&b64, base64::STANDARD)
+            .map_err(|_| de::Error::custom("invalid base64 certificate"))?;
+        Ok(Self(der))
+    }
+}
+
+impl Base64DerCertificate {
+    /// Returns the PEM encoding of this certificate.
+    pub fn to_pem(&self) -> String {
+        let b64 = base64::encode(&self.0);
+        let mut pem = String::from("-----BEGIN CERTIFICATE-----\n");
+        for line in b64.as_bytes().chunks(64) {
+            pem.push_str(std::str::from_utf8(line).unwrap());
+            pem.push('\n');
+        }
+        pem.push_str("-----END CERTIFICATE-----\n");
+        pem
+    }
+}
+
+#[cfg(feature = "crypto")]
+const ECDSA_WITH_SHA256_OID: &[u64] = &[1, 2, 840, 10045, 4, 3, 2];
+#[cfg(feature = "crypto")]
+const ECDSA_WITH_SHA384_OID: &[u64] = &[1, 2, 840, 10045, 4, 3, 3];
+#[cfg(feature = "crypto")]
+const ED25519_OID: &[u64] = &[1, 3, 101, 112];
+#[cfg(feature = "crypto")]
+const SHA256_WITH_RSA_ENCRYPTION_OID: &[u64] = &[1, 2, 840, 113549, 1, 1, 11];
+#[cfg(feature = "crypto")]
+const SHA384_WITH_RSA_ENCRYPTION_OID: &[u64] = &[1, 2, 840, 113549, 1, 1, 12];
+#[cfg(feature = "crypto")]
+const SHA512_WITH_RSA_ENCRYPTION_OID: &[u64] = &[1, 2, 840, 113549, 1, 1, 13];
+#[cfg(feature = "crypto")]
+const COMMON_NAME_OID: &[u64] = &[2, 5, 4, 3];
+
+#[cfg(feature = "crypto")]
+impl crate::JsonWebKey {
+    /// Generates a self-signed X.509 v3 certificate for this private key, having `subject` as the
+    /// common name of both its subject and issuer and being valid from now for `validity`.
+    /// P-256, P-384, Ed25519, and RSA (of at least 2048 bits, signing with SHA-256) keys are
+    /// supported. The certificate may be published in the `x5c` member or used for mTLS.
+    pub fn generate_self_signed_cert(
+        &self,
+        subject: &str,
+        validity: std::time::Duration,
+    ) -> Result<Base64DerCertificate, crate::ConversionError> {
+        use num_bigint::BigUint;
+        use ring::{rand::SecureRandom, signature};
+        use yasna::{models::ObjectIdentifier, DERWriter, Tag};
+
+        use crate::{utils::x509::write_time, ConversionError, Curve, Key, OkpCurve};
+
+        // `ring` errors carry no detail beyond the key being unusable.
+        fn unsupported_key<E>(_: E) -> ConversionError {
+            ConversionError::UnsupportedKey
+        }
+
+        if !self.key.is_private() {
+            return Err(ConversionError::NotPrivate);
+        }
+        let rng = ring::rand::SystemRandom::new();
+
+        type Signer = Box<dyn Fn(&[u8]) -> Result<Vec<u8>, ring::error::Unspecified>>;
+        let ecdsa = |alg, d: &[u8], x: &[u8], y: &[u8]| -> Result<Signer, ConversionError> {
+            let public = [&[0x04][..], x, y].concat();
+            let key_pair =
+                signature::EcdsaKeyPair::from_private_key_and_public_key(alg, d, &public)
+                    .map_err(unsupported_key)?;
+            let rng = rng.clone();
+            Ok(Box::new(move |tbs| {
+                Ok(key_pair.sign(&rng, tbs)?.as_ref().to_vec())
+            }))
+        };
+        let (signature_algorithm, sign): (&[u64], Signer) = match &*self.key {
+            Key::EC {
+                curve: Curve::P256 { d: Some(d), x, y },
+            } => (
+                ECDSA_WITH_SHA256_OID,
+                ecdsa(&signature::ECDSA_P256_SHA256_ASN1_SIGNING, d, x, y)?,
+            ),
+            Key::EC {
+                curve: Curve::P384 { d: Some(d), x, y },
+            } => (
+                ECDSA_WITH_SHA384_OID,
+                ecdsa(&signature::ECDSA_P384_SHA384_ASN1_SIGNING, d, x, y)?,
+            ),
+            Key::OKP {
+                curve: OkpCurve::Ed25519 { d: Some(d), x },
+            } => {
+                let key_pair = signature::Ed25519KeyPair::from_seed_and_public_key(d, x)
+                    .map_err(unsupported_key)?;
+                (
+                    ED25519_OID,
+                    Box::new(move |tbs| Ok(key_pair.sign(tbs).as_ref().to_vec())),
+                )
+            }
+            Key::RSA { .. } => {
+                let key_pair = signature::RsaKeyPair::from_pkcs8(&self.key.try_to_der()?)
+                    .map_err(unsupported_key)?;
+                let rng = rng.clone();
+                (
+                    SHA256_WITH_RSA_ENCRYPTION_OID,
+                    Box::new(move |tbs| {
+                        let mut sig = vec![0; key_pair.public_modulus_len()];
+                        key_pair.sign(&signature::RSA_PKCS1_SHA256, &rng, tbs, &mut sig)?;
+                        Ok(sig)
+                    }),
+                )
+            }
+            _ => return Err(ConversionError::UnsupportedKey),
+        };
+        let write_signature_algorithm = |writer: DERWriter| {
+            writer.write_sequence(|writer| {
+                writer
+                    .next()
+                    .write_oid(&ObjectIdentifier::from_slice(signature_algorithm));
+                if signature_algorithm == SHA256_WITH_RSA_ENCRYPTION_OID {
+                    writer.next().write_null();
+                }
+            })
+        };
+        let write_name = |writer: DERWriter| {
+            writer.write_sequence(|writer| {
+                writer.next().write_set(|writer| {
+                    writer.next().write_sequence(|writer| {
+                        writer
+                            .next()
+                            .write_oid(&ObjectIdentifier::from_slice(COMMON_NAME_OID));
+                        writer.next().write_utf8_string(subject);
+                    })
+                })
+            })
+        };
+
+        let mut serial = [0u8; 16];
+        rng.fill(&mut serial).map_err(unsupported_key)?;
+        serial[0] &= 0x7f;
+        let not_before = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|now| now.as_secs())
+            .unwrap_or_default();
+        let not_after = not_before.saturating_add(validity.as_secs());
+        let public_key_info = self.key.to_public().unwrap().try_to_der()?;
+
+        let tbs = yasna::construct_der(|writer| {
+            writer.write_sequence(|writer| {
+                writer
+                    .next()
+                    .write_tagged(Tag::context(0), |writer| writer.write_u8(2)); // v3
+                writer
+                    .next()
+                    .write_biguint(&BigUint::from_bytes_be(&serial));
+                write_signature_algorithm(writer.next());
+                write_name(writer.next());
+                writer.next().write_sequence(|writer| {
+                    write_time(writer.next(), not_before);
+                    write_time(writer.next(), not_after);
+                });
+                write_name(writer.next());
+                writer.next().write_der(&public_key_info);
+            })
+        });
+        let signature = sign(&tbs).map_err(unsupported_key)?;
+        Ok(Base64DerCertificate(yasna::construct_der(|writer| {
+            writer.write_sequence(|writer| {
+                writer.next().write_der(&tbs);
+                write_signature_algorithm(writer.next());
+                writer
+                    .next()
+                    .write_bitvec_bytes(&signature, signature.len() * 8);
+            })
+        })))
+    }
+}
+
+/// Checks that `certificate` was signed by the key of `issuer`.
+#[cfg(feature = "crypto")]
+fn verify_issued_by(
+    certificate: &crate::utils::x509::Certificate,
+    issuer: &crate::utils::x509::Certificate,
+) -> Result<(), crate::Error> {
+    use ring::signature;
+
+    use crate::{utils::pkcs8, ConversionError, Curve, Error, Key, OkpCurve};
+
+    let issuer_key = Key::from_spki_der(&issuer.public_key_info)?;
+    let (_, public_key) =
+        pkcs8::read_public(&issuer.public_key_info).map_err(ConversionError::from)?;
+    let algorithm: &'static dyn signature::VerificationAlgorithm = match (
+        certificate.signature_algorithm.components().as_slice(),
+        &issuer_key,
+    ) {
+        (ECDSA_WITH_SHA256_OID, Key::EC { curve }) => match curve {
+            Curve::P256 { .. } => &signature::ECDSA_P256_SHA256_ASN1,
+            Curve::P384 { .. } => &signature::ECDSA_P384_SHA256_ASN1,
+            _ => return Err(Error::InvalidCertificateChain("unsupported issuer key")),
+        },
+        (ECDSA_WITH_SHA384_OID, Key::EC { curve }) => match curve {
+            Curve::P256 { .. } => &signature::ECDSA_P256_SHA384_ASN1,
+            Curve::P384 { .. } => &signature::ECDSA_P384_SHA384_ASN1,
+            _ => return Err(Error::InvalidCertificateChain("unsupported issuer key")),
+        },
+        (
+            ED25519_OID,
+            Key::OKP {
+                curve: OkpCurve::Ed25519 { .. },
+            },
+        ) => &signature::ED25519,
+        (SHA256_WITH_RSA_ENCRYPTION_OID, Key::RSA { .. }) => &signature::RSA_PKCS1_2048_8192_SHA256,
+        (SHA384_WITH_RSA_ENCRYPTION_OID, Key::RSA { .. }) => &signature::RSA_PKCS1_2048_8192_SHA384,
+        (SHA512_WITH_RSA_ENCRYPTION_OID, Key::RSA { .. }) => &signature::RSA_PKCS1_2048_8192_SHA512,
+        _ => {
+            return Err(Error::InvalidCertificateChain(
+                "unsupported signature algorithm",
+            ))
+        }
+    };
+    signature::UnparsedPublicKey::new(algorithm, &public_key)
+        .verify(&certificate.tbs, &certificate.signature)
+        .map_err(|_| Error::InvalidCertificateChain("invalid certificate signature"))
+}
+
+#[cfg(feature = "crypto")]
+impl crate::JsonWebKey {
+    /// Orders `certificates`, which must contain the certificate of this key and may contain its
+    /// intermediates and root in any order, into an `x5c` chain. Each certificate is checked to be
+    /// signed by the next one, and every supplied certificate must be part of the chain. The chain
+    /// ends with a self-issued certificate or with the last available issuer.
+    /// The `x5t` and `x5t#S256` members are updated if already present.
+    pub fn set_certificate_chain(
+        &mut self,
+        certificates: Vec<Base64DerCertificate>,
+    ) -> Result<(), crate::Error> {
+        use crate::{utils::x509, ConversionError, Error, Key};
+
+        let mut remaining = certificates
+            .into_iter()
+            .map(|der| Ok((x509::read_certificate(&der)?, der)))
+            .collect::<Result<Vec<_>, yasna::ASN1Error>>()
+            .map_err(ConversionError::from)?;
+        let leaf = remaining
+            .iter()
+            .position(|(certificate, _)| {
+                Key::from_spki_der(&certificate.public_key_info)
+                    .is_ok_and(|key| key.public_eq(&self.key))
+            })
+            .ok_or(Error::CertificateMismatch)?;
+        let mut chain = vec![remaining.remove(leaf)];
+        while !remaining.is_empty() {
+            let current = &chain.last().unwrap().0;
+            if current.issuer == current.subject {
+                return Err(Error::InvalidCertificateChain(
+                    "certificate not part of the chain",
+                ));
+            }
+            let mut issuers = remaining
+                .iter()
+                .enumerate()
+                .filter(|(_, (certificate, _))| certificate.subject == current.issuer)
+                .peekable();
+            if issuers.peek().is_none() {
+                return Err(Error::InvalidCertificateChain(
+                    "certificate not part of the chain",
+                ));
+            }
+            let mut result = Ok(0);
+            for (i, (issuer, _)) in issuers {
+                result = verify_issued_by(current, issuer).map(|_| i);
+                if result.is_ok() {
+                    break;
+                }
+            }
+            chain.push(remaining.remove(result?));
+        }
+
+        let had_thumbprints = self.certificate_sha1_thumbprint.is_some()
+            || self.certificate_sha256_thumbprint.is_some();
+        self.certificate_chain = Some(chain.into_iter().map(|(_, der)| der).collect());
+        if had_thumbprints {
+            self.set_certificate_thumbprints();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serde_certificate() {
+        let cert = Base64DerCertificate(vec![0xfb, 0xff, 0x01]);
+        let json = serde_json::to_string(&cert).unwrap();
+        assert_eq!(json, r#""+/8B""#);
+        assert_eq!(
+            serde_json::from_str::<Base64DerCertificate>(&json).unwrap(),
+            cert
+        );
+        assert!(serde_json::from_str::<Base64DerCertificate>(r#""-_8B""#).is_err());
+    }
+}
@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+
+use crate::JsonWebKey;
+
+/// The value of a `cnf` (confirmation) claim, binding a token to a proof-of-possession key as per
+/// [RFC 7800](https://tools.ietf.org/html/rfc7800) and, for `jkt`,
+/// [RFC 9449](https://tools.ietf.org/html/rfc9449#section-6).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Confirmation {
+    /// The public proof-of-possession key itself (`{"jwk": {...}}`).
+    #[serde(rename = "jwk")]
+    Jwk(Box<JsonWebKey>),
+    /// The RFC 7638 SHA-256 thumbprint of the proof-of-possession key (`{"jkt": "..."}`).
+    #[serde(rename = "jkt")]
+    Thumbprint(String),
+    /// The `kid` of a proof-of-possession key known to the recipient (`{"kid": "..."}`).
+    #[serde(rename = "kid")]
+    KeyId(String),
+}
+
+impl Confirmation {
+    /// Returns a confirmation embedding the public part of `jwk`, along with its metadata.
+    /// Returns `None` if the key has no public part, as for symmetric keys.
+    pub fn jwk(jwk: &JsonWebKey) -> Option<Self> {
+        let public = jwk.key.to_public()?.into_owned();
+        Some(Self::Jwk(Box::new(JsonWebKey {
+            key: Box::new(public),
+            ..jwk.clone()
+        })))
+    }
+
+    /// Returns a confirmation referencing `jwk` by its thumbprint.
+    pub fn thumbprint(jwk: &JsonWebKey) -> Self {
+        Self::Thumbprint(jwk.key.thumbprint())
+    }
+
+    /// Returns true iff `jwk` is the confirmed key. A key confirmed by its `kid` matches any key
+    /// having that `kid`, so the recipient must ensure it only looks up keys it trusts.
+    pub fn matches(&self, jwk: &JsonWebKey) -> bool {
+        match self {
+            Self::Jwk(confirmed) => confirmed.key.public_eq(&jwk.key),
+            Self::Thumbprint(thumbprint) => *thumbprint == jwk.key.thumbprint(),
+            Self::KeyId(kid) => jwk.key_id.as_ref() == Some(kid),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::Key;
+
+    static P256_JWK: &str = r#"{
+        "kty": "EC",
+        "crv": "P-256",
+        "d": "ZoKQ9j4dhIBlMRVrv-QG8P_T9sutv3_95eio9MtpgKg",
+        "x": "QOMHmv96tVlJv-uNqprnDSKIj5AiLTXKRomXYnav0N0",
+        "y": "TjYZoHnctatEE6NCrKmXQdJJPnNzZEX8nBmZde3AY4k",
+        "kid": "pop"
+    }"#;
+
+    #[test]
+    fn jwk_confirmation() {
+        let jwk: JsonWebKey = P256_JWK.parse().unwrap();
+        let cnf = Confirmation::jwk(&jwk).unwrap();
+        let json = serde_json::to_value(&cnf).unwrap();
+        assert_eq!(json["jwk"]["kid"], "pop");
+        assert!(json["jwk"].get("d").is_none());
+        assert_eq!(serde_json::from_value::<Confirmation>(json).unwrap(), cnf);
+        assert!(cnf.matches(&jwk));
+
+        let symmetric = JsonWebKey::new(Key::Symmetric {
+            key: vec![42; 32].into(),
+        });
+        assert!(Confirmation::jwk(&symmetric).is_none());
+        assert!(!cnf.matches(&symmetric));
+    }
+
+    #[test]
+    fn thumbprint_and_kid_confirmation() {
+        let jwk: JsonWebKey = P256_JWK.parse().unwrap();
+        let cnf = Confirmation::thumbprint(&jwk);
+        assert_eq!(
+            serde_json::to_string(&cnf).unwrap(),
+            format!(r#"{{"jkt":"{}"}}"#, jwk.key.thumbprint())
+        );
+        assert!(cnf.matches(&jwk));
+
+        let cnf: Confirmation = serde_json::from_str(r#"{"kid":"pop"}"#).unwrap();
+        assert_eq!(cnf, Confirmation::KeyId("pop".into()));
+        assert!(cnf.matches(&jwk));
+        assert!(!cnf.matches(&JsonWebKey::new(*jwk.key)));
+    }
+}
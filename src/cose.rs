@@ -0,0 +1,365 @@
+//! Conversion between `Key` and the COSE_Key CBOR structure
+//! ([RFC 9052 §7](https://tools.ietf.org/html/rfc9052#section-7),
+//! [RFC 9053 §7](https://tools.ietf.org/html/rfc9053#section-7), and
+//! [RFC 8230 §4](https://tools.ietf.org/html/rfc8230#section-4)).
+
+use crate::{ConversionError, Curve, Key, OkpCurve, PublicExponent, RsaPrivate, RsaPublic};
+
+/// The common COSE_Key label of the key type.
+const KTY: i64 = 1;
+
+/// The COSE key types.
+const KTY_OKP: i64 = 1;
+const KTY_EC2: i64 = 2;
+const KTY_RSA: i64 = 3;
+const KTY_SYMMETRIC: i64 = 4;
+
+/// The COSE identifiers of the supported elliptic curves and their `crv` names.
+const CURVES: &[(i64, &str)] = &[
+    (1, "P-256"),
+    (2, "P-384"),
+    (3, "P-521"),
+    (4, "X25519"),
+    (5, "X448"),
+    (6, "Ed25519"),
+    (7, "Ed448"),
+    (8, "secp256k1"),
+    (256, "brainpoolP256r1"),
+    (257, "brainpoolP384r1"),
+    (258, "brainpoolP512r1"),
+];
+
+/// The key type-specific labels of the EC2 and OKP parameters.
+const CRV: i64 = -1;
+const X: i64 = -2;
+const Y: i64 = -3;
+const D: i64 = -4;
+
+/// The key type-specific label of the symmetric key value.
+const K: i64 = -1;
+
+/// The key type-specific labels of the RSA parameters, in order.
+const RSA_N: i64 = -1;
+const RSA_E: i64 = -2;
+const RSA_D: i64 = -3;
+const RSA_P: i64 = -4;
+const RSA_Q: i64 = -5;
+const RSA_DP: i64 = -6;
+const RSA_DQ: i64 = -7;
+const RSA_QI: i64 = -8;
+
+/// The most deeply nested item that will be skipped when reading unknown parameters.
+const MAX_DEPTH: usize = 16;
+
+/// The CBOR major types used by COSE_Key.
+const MAJOR_UINT: u8 = 0;
+const MAJOR_NEGINT: u8 = 1;
+const MAJOR_BYTES: u8 = 2;
+const MAJOR_TEXT: u8 = 3;
+const MAJOR_ARRAY: u8 = 4;
+const MAJOR_MAP: u8 = 5;
+const MAJOR_TAG: u8 = 6;
+
+/// A decoded CBOR data item, for the purposes of reading a COSE_Key.
+enum Value<'a> {
+    Int(i64),
+    Bytes(&'a [u8]),
+    Other,
+}
+
+/// Reads the subset of [CBOR](https://tools.ietf.org/html/rfc8949) used by COSE_Key.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn read_byte(&mut self) -> Result<u8, ConversionError> {
+        let (byte, rest) = self.0.split_first().ok_or(ConversionError::InvalidCose)?;
+        self.0 = rest;
+        Ok(*byte)
+    }
+
+    fn read_slice(&mut self, len: u64) -> Result<&'a [u8], ConversionError> {
+        if (self.0.len() as u64) < len {
+            return Err(ConversionError::InvalidCose);
+        }
+        let (slice, rest) = self.0.split_at(len as usize);
+        self.0 = rest;
+        Ok(slice)
+    }
+
+    /// Reads the initial byte and argument of a data item. Indefinite lengths are rejected.
+    fn read_head(&mut self) -> Result<(u8, u64), ConversionError> {
+        let initial = self.read_byte()?;
+        let argument = match initial & 0x1f {
+            n @ 0..=23 => u64::from(n),
+            n @ 24..=27 => {
+                let len = 1 << (n - 24);
+                self.read_slice(len)?
+                    .iter()
+                    .fold(0, |arg, b| (arg << 8) | u64::from(*b))
+            }
+            _ => return Err(ConversionError::InvalidCose),
+        };
+        Ok((initial >> 5, argument))
+    }
+
+    fn read_value(&mut self, depth: usize) -> Result<Value<'a>, ConversionError> {
+        if depth > MAX_DEPTH {
+            return Err(ConversionError::InvalidCose);
+        }
+        let (major, argument) = self.read_head()?;
+        Ok(match major {
+            MAJOR_UINT | MAJOR_NEGINT => {
+                if argument > i64::MAX as u64 {
+                    return Err(ConversionError::InvalidCose);
+                }
+                let n = argument as i64;
+                Value::Int(if major == MAJOR_UINT { n } else { -1 - n })
+            }
+            MAJOR_BYTES => Value::Bytes(self.read_slice(argument)?),
+            MAJOR_TEXT => {
+                self.read_slice(argument)?;
+                Value::Other
+            }
+            MAJOR_ARRAY | MAJOR_MAP => {
+                // Every item takes at least one byte, which also keeps the count from overflowing.
+                if argument > self.0.len() as u64 {
+                    return Err(ConversionError::InvalidCose);
+                }
+                let items = if major == MAJOR_MAP { 2 } else { 1 } * argument;
+                for _ in 0..items {
+                    self.read_value(depth + 1)?;
+                }
+                Value::Other
+            }
+            MAJOR_TAG => {
+                self.read_value(depth + 1)?;
+                Value::Other
+            }
+            _ => Value::Other, // A simple value or float.
+        })
+    }
+}
+
+/// Writes canonical CBOR.
+#[derive(Default)]
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn write_head(&mut self, major: u8, argument: u64) {
+        let major = major << 5;
+        match argument {
+            0..=23 => self.0.push(major | argument as u8),
+            24..=0xff => self.0.extend_from_slice(&[major | 24, argument as u8]),
+            0x100..=0xffff => {
+                self.0.push(major | 25);
+                self.0.extend_from_slice(&(argument as u16).to_be_bytes());
+            }
+            0x1_0000..=0xffff_ffff => {
+                self.0.push(major | 26);
+                self.0.extend_from_slice(&(argument as u32).to_be_bytes());
+            }
+            _ => {
+                self.0.push(major | 27);
+                self.0.extend_from_slice(&argument.to_be_bytes());
+            }
+        }
+    }
+
+    fn write_int(&mut self, n: i64) {
+        if n >= 0 {
+            self.write_head(MAJOR_UINT, n as u64);
+        } else {
+            self.write_head(MAJOR_NEGINT, (-1 - n) as u64);
+        }
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        self.write_head(MAJOR_BYTES, bytes.len() as u64);
+        self.0.extend_from_slice(bytes);
+    }
+}
+
+/// A COSE_Key parameter value, for the purposes of writing.
+enum Param<'a> {
+    Int(i64),
+    Bytes(&'a [u8]),
+}
+
+fn curve_id(crv: &str) -> Result<i64, ConversionError> {
+    CURVES
+        .iter()
+        .find(|(_, name)| *name == crv)
+        .map(|(id, _)| *id)
+        .ok_or(ConversionError::UnsupportedKey)
+}
+
+impl Key {
+    /// Encodes this key as a COSE_Key, using the deterministic encoding of
+    /// [RFC 8949 §4.2](https://tools.ietf.org/html/rfc8949#section-4.2).
+    /// Only the key type and key type-specific parameters are written.
+    pub fn to_cose_key(&self) -> Result<Vec<u8>, ConversionError> {
+        let mut params = Vec::new();
+        match self {
+            Self::EC { curve } => {
+                let (d, x, y) = curve.components().ok_or(ConversionError::UnsupportedKey)?;
+                params.push((KTY, Param::Int(KTY_EC2)));
+                params.push((CRV, Param::Int(curve_id(curve.name())?)));
+                params.push((X, Param::Bytes(x)));
+                params.push((Y, Param::Bytes(y)));
+                if let Some(d) = d {
+                    params.push((D, Param::Bytes(d)));
+                }
+            }
+            Self::OKP { curve } => {
+                let (d, x) = curve.components();
+                params.push((KTY, Param::Int(KTY_OKP)));
+                params.push((CRV, Param::Int(curve_id(curve.name())?)));
+                params.push((X, Param::Bytes(x)));
+                if let Some(d) = d {
+                    params.push((D, Param::Bytes(d)));
+                }
+            }
+            Self::RSA { public, private } => {
+                params.push((KTY, Param::Int(KTY_RSA)));
+                params.push((RSA_N, Param::Bytes(&public.n)));
+                params.push((RSA_E, Param::Bytes(&[0x01, 0x00, 0x01])));
+                if let Some(private) = private {
+                    params.push((RSA_D, Param::Bytes(&private.d)));
+                    for (label, value) in &[
+                        (RSA_P, &private.p),
+                        (RSA_Q, &private.q),
+                        (RSA_DP, &private.dp),
+                        (RSA_DQ, &private.dq),
+                        (RSA_QI, &private.qi),
+                    ] {
+                        if let Some(value) = value {
+                            params.push((*label, Param::Bytes(value)));
+                        }
+                    }
+                }
+            }
+            Self::Symmetric { key } => {
+                params.push((KTY, Param::Int(KTY_SYMMETRIC)));
+                params.push((K, Param::Bytes(key)));
+            }
+            Self::Unknown { .. } => return Err(ConversionError::UnsupportedKey),
+        }
+
+        // The parameters are pushed in the deterministic order of their labels:
+        // positive labels ascending, then negative labels descending.
+        let mut writer = Writer::default();
+        writer.write_head(MAJOR_MAP, params.len() as u64);
+        for (label, value) in params {
+            writer.write_int(label);
+            match value {
+                Param::Int(n) => writer.write_int(n),
+                Param::Bytes(bytes) => writer.write_bytes(bytes),
+            }
+        }
+        Ok(writer.0)
+    }
+
+    /// Decodes a COSE_Key of type OKP, EC2, RSA, or Symmetric. Parameters other than the
+    /// key type-specific ones (e.g., `kid` and `alg`) are ignored.
+    /// EC2 keys having a compressed point are not supported.
+    pub fn from_cose_key(cbor: &[u8]) -> Result<Self, ConversionError> {
+        let mut reader = Reader(cbor);
+        let (major, len) = reader.read_head()?;
+        if major != MAJOR_MAP {
+            return Err(ConversionError::InvalidCose);
+        }
+        let mut params: Vec<(i64, Value)> = Vec::new();
+        for _ in 0..len {
+            let label = match reader.read_value(1)? {
+                Value::Int(label) => Some(label),
+                Value::Bytes(_) => return Err(ConversionError::InvalidCose),
+                Value::Other => None, // A text string label.
+            };
+            let value = reader.read_value(1)?;
+            if let Some(label) = label {
+                if params.iter().any(|(l, _)| *l == label) {
+                    return Err(ConversionError::InvalidCose);
+                }
+                params.push((label, value));
+            }
+        }
+        if !reader.0.is_empty() {
+            return Err(ConversionError::InvalidCose);
+        }
+
+        let param = |label: i64| params.iter().find(|(l, _)| *l == label).map(|(_, v)| v);
+        let int = |label| match param(label) {
+            Some(Value::Int(n)) => Ok(*n),
+            _ => Err(ConversionError::InvalidCose),
+        };
+        let opt_bytes = |label| match param(label) {
+            Some(Value::Bytes(bytes)) => Ok(Some(*bytes)),
+            Some(_) => Err(ConversionError::InvalidCose),
+            None => Ok(None),
+        };
+        let bytes = |label| opt_bytes(label)?.ok_or(ConversionError::InvalidCose);
+        let crv = |label| {
+            let id = int(label)?;
+            CURVES
+                .iter()
+                .find(|(i, _)| *i == id)
+                .map(|(_, name)| *name)
+                .ok_or(ConversionError::UnsupportedKey)
+        };
+
+        Ok(match int(KTY)? {
+            KTY_EC2 => {
+                let crv = crv(CRV)?;
+                if !Curve::is_supported(crv) {
+                    return Err(ConversionError::UnsupportedKey);
+                }
+                let y = match param(Y) {
+                    Some(Value::Bytes(y)) => y,
+                    Some(Value::Other) => return Err(ConversionError::UnsupportedKey),
+                    _ => return Err(ConversionError::InvalidCose),
+                };
+                Self::EC {
+                    curve: Curve::from_components(crv, opt_bytes(D)?, bytes(X)?, y)
+                        .map_err(ConversionError::InvalidLength)?,
+                }
+            }
+            KTY_OKP => {
+                let crv = crv(CRV)?;
+                if !OkpCurve::is_supported(crv) {
+                    return Err(ConversionError::UnsupportedKey);
+                }
+                Self::OKP {
+                    curve: OkpCurve::from_components(crv, opt_bytes(D)?, bytes(X)?)
+                        .map_err(ConversionError::InvalidLength)?,
+                }
+            }
+            KTY_RSA => {
+                let e = bytes(RSA_E)?;
+                if e.iter().skip_while(|b| **b == 0).ne(&[0x01, 0x00, 0x01]) {
+                    return Err(ConversionError::UnsupportedKey);
+                }
+                let public = RsaPublic {
+                    e: PublicExponent,
+                    n: bytes(RSA_N)?.to_vec().into(),
+                };
+                let crt = |label| opt_bytes(label).map(|v| v.map(|v| v.to_vec().into()));
+                let private = match opt_bytes(RSA_D)? {
+                    Some(d) => Some(RsaPrivate {
+                        d: d.to_vec().into(),
+                        p: crt(RSA_P)?,
+                        q: crt(RSA_Q)?,
+                        dp: crt(RSA_DP)?,
+                        dq: crt(RSA_DQ)?,
+                        qi: crt(RSA_QI)?,
+                    }),
+                    None => None,
+                };
+                Self::RSA { public, private }
+            }
+            KTY_SYMMETRIC => Self::Symmetric {
+                key: bytes(K)?.to_vec().into(),
+            },
+            _ => return Err(ConversionError::UnsupportedKey),
+        })
+    }
+}
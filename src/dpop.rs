@@ -0,0 +1,65 @@
+//! Helpers for binding tokens to keys with
+//! [DPoP](https://tools.ietf.org/html/rfc9449) (Demonstrating Proof of Possession).
+
+use crate::JsonWebKey;
+
+/// Returns the JWK SHA-256 thumbprint (`jkt`) to which tokens are bound for `jwk`.
+pub fn jkt(jwk: &JsonWebKey) -> String {
+    jwk.key.thumbprint()
+}
+
+/// Returns the public key to be placed in the `jwk` header of a DPoP proof, stripped of all
+/// other members. Returns `None` if the key has no public part, as for symmetric keys.
+pub fn proof_jwk(jwk: &JsonWebKey) -> Option<JsonWebKey> {
+    Some(JsonWebKey::new(jwk.key.to_public()?.into_owned()))
+}
+
+/// Returns true iff the `jwk` header of a DPoP proof is the key bound by `jkt`. As proofs must not
+/// carry private or symmetric keys, such keys never match.
+pub fn matches_jkt(presented: &JsonWebKey, jkt: &str) -> bool {
+    !presented.key.is_private() && presented.key.thumbprint() == jkt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::Key;
+
+    static P256_JWK: &str = r#"{
+        "kty": "EC",
+        "crv": "P-256",
+        "d": "ZoKQ9j4dhIBlMRVrv-QG8P_T9sutv3_95eio9MtpgKg",
+        "x": "QOMHmv96tVlJv-uNqprnDSKIj5AiLTXKRomXYnav0N0",
+        "y": "TjYZoHnctatEE6NCrKmXQdJJPnNzZEX8nBmZde3AY4k",
+        "kid": "pop",
+        "alg": "ES256"
+    }"#;
+
+    #[test]
+    fn dpop_binding() {
+        let jwk: JsonWebKey = P256_JWK.parse().unwrap();
+        let proof_jwk = proof_jwk(&jwk).unwrap();
+        assert_eq!(
+            serde_json::to_value(&proof_jwk).unwrap(),
+            serde_json::json!({
+                "kty": "EC",
+                "crv": "P-256",
+                "x": "QOMHmv96tVlJv-uNqprnDSKIj5AiLTXKRomXYnav0N0=",
+                "y": "TjYZoHnctatEE6NCrKmXQdJJPnNzZEX8nBmZde3AY4k=",
+            })
+        );
+
+        let jkt = jkt(&jwk);
+        assert_eq!(jkt, proof_jwk.key.thumbprint());
+        assert!(matches_jkt(&proof_jwk, &jkt));
+        assert!(!matches_jkt(&jwk, &jkt));
+        assert!(!matches_jkt(&proof_jwk, "bm90IHRoZSBqa3Q"));
+
+        let symmetric = JsonWebKey::new(Key::Symmetric {
+            key: vec![42; 32].into(),
+        });
+        assert!(super::proof_jwk(&symmetric).is_none());
+        assert!(!matches_jkt(&symmetric, &super::jkt(&symmetric)));
+    }
+}
@@ -0,0 +1,124 @@
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use crate::{utils::curve25519, Algorithm, ConversionError, Curve, Error, Key, OkpCurve};
+
+impl Key {
+    /// Derives the key agreed upon by this private key and the public key `peer_public` using
+    /// ECDH-ES with the Concat KDF, as per
+    /// [RFC 7518 §4.6](https://tools.ietf.org/html/rfc7518#section-4.6). `apu` and `apv` are the
+    /// decoded values of the `apu` and `apv` header members (empty if absent).
+    ///
+    /// For key agreement with key wrapping, `alg` is one of the ECDH-ES+A*KW algorithms and the
+    /// derived key is the AES key wrapping key. For direct key agreement (`ECDH-ES`), `alg` is
+    /// instead the `enc` algorithm and the derived key is the content encryption key.
+    /// P-256 and X25519 keys are supported.
+    pub fn derive_shared_secret(
+        &self,
+        peer_public: &Key,
+        alg: &Algorithm,
+        apu: &[u8],
+        apv: &[u8],
+    ) -> Result<Key, Error> {
+        let key_len = match alg {
+            Algorithm::EcdhEsA128KW => 16,
+            Algorithm::EcdhEsA192KW => 24,
+            Algorithm::EcdhEsA256KW => 32,
+            alg => alg.content_key_len().ok_or(Error::MismatchedAlgorithm)?,
+        };
+        let shared_secret = match (self, peer_public) {
+            (
+                Self::EC {
+                    curve: Curve::P256 { d: Some(d), .. },
+                },
+                Self::EC {
+                    curve: Curve::P256 { x, y, .. },
+                },
+            ) => p256_ecdh(d, x, y)?,
+            (
+                Self::OKP {
+                    curve: OkpCurve::X25519 { d: Some(d), .. },
+                },
+                Self::OKP {
+                    curve: OkpCurve::X25519 { x, .. },
+                },
+            ) => {
+                let mut scalar = Zeroizing::new([0; 32]);
+                scalar.copy_from_slice(d);
+                let mut u = [0; 32];
+                u.copy_from_slice(x);
+                let shared_secret = Zeroizing::new(curve25519::x25519(&scalar, &u).to_vec());
+                // Low-order points yield the all-zero value (RFC 7748 §6.1).
+                if shared_secret.iter().all(|b| *b == 0) {
+                    return Err(Error::InvalidPublicKey);
+                }
+                shared_secret
+            }
+            _ if !self.is_private() => return Err(ConversionError::NotPrivate.into()),
+            (Self::EC { curve }, Self::EC { curve: peer_curve })
+                if curve.name() != peer_curve.name() =>
+            {
+                return Err(Error::InvalidPublicKey)
+            }
+            (Self::OKP { curve }, Self::OKP { curve: peer_curve })
+                if curve.name() != peer_curve.name() =>
+            {
+                return Err(Error::InvalidPublicKey)
+            }
+            _ => return Err(ConversionError::UnsupportedKey.into()),
+        };
+        let key = concat_kdf(&shared_secret, &alg.to_string(), apu, apv, key_len);
+        Ok(Self::Symmetric { key: key.into() })
+    }
+}
+
+/// Returns the x coordinate of `d` times the point (`x`, `y`), which must be on the curve.
+fn p256_ecdh(d: &[u8], x: &[u8], y: &[u8]) -> Result<Zeroizing<Vec<u8>>, Error> {
+    use p256::arithmetic::{AffinePoint, ProjectivePoint, Scalar};
+
+    let public = [&[0x04][..], x, y].concat();
+    let point = p256::PublicKey::from_bytes(&public)
+        .and_then(|pk| Option::<AffinePoint>::from(AffinePoint::from_pubkey(&pk)))
+        .ok_or(Error::InvalidPublicKey)?;
+    let mut scalar_bytes = Zeroizing::new([0; 32]);
+    scalar_bytes.copy_from_slice(d);
+    let scalar = Option::<Scalar>::from(Scalar::from_bytes(*scalar_bytes))
+        .ok_or(ConversionError::UnsupportedKey)?;
+    let shared_point =
+        Option::<AffinePoint>::from((ProjectivePoint::from(point) * &scalar).to_affine())
+            .ok_or(Error::InvalidPublicKey)?;
+    Ok(Zeroizing::new(
+        shared_point.to_uncompressed_pubkey().as_bytes()[1..33].to_vec(),
+    ))
+}
+
+/// The Concat KDF of NIST SP 800-56A §5.8.1 using SHA-256, with the `OtherInfo` of
+/// RFC 7518 §4.6.2.
+fn concat_kdf(
+    shared_secret: &[u8],
+    algorithm_id: &str,
+    apu: &[u8],
+    apv: &[u8],
+    key_len: usize,
+) -> Vec<u8> {
+    let with_len = |data: &[u8]| [&(data.len() as u32).to_be_bytes()[..], data].concat();
+    let other_info = [
+        with_len(algorithm_id.as_bytes()),
+        with_len(apu),
+        with_len(apv),
+        ((8 * key_len) as u32).to_be_bytes().to_vec(),
+    ]
+    .concat();
+    let mut key = Vec::with_capacity(key_len + 32);
+    let mut counter = 1u32;
+    while key.len() < key_len {
+        let mut hasher = Sha256::new();
+        hasher.update(counter.to_be_bytes());
+        hasher.update(shared_secret);
+        hasher.update(&other_info);
+        key.extend_from_slice(&hasher.finalize());
+        counter += 1;
+    }
+    key.truncate(key_len);
+    key
+}
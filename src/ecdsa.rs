@@ -0,0 +1,135 @@
+//! Conversions of ECDSA signatures between the ASN.1 DER `Ecdsa-Sig-Value` form produced by
+//! OpenSSL, PKCS#11 tokens, and most HSMs, and the fixed-size `r || s` form used by JWS
+//! ([RFC 7518 §3.4](https://tools.ietf.org/html/rfc7518#section-3.4)).
+
+use crate::ConversionError;
+
+/// Converts a DER-encoded `SEQUENCE { r INTEGER, s INTEGER }` into the JWS form, in which `r`
+/// and `s` are left-padded to `scalar_len` bytes each, e.g. 32 for P-256, 48 for P-384, and 66
+/// for P-521.
+pub fn der_to_fixed(der: &[u8], scalar_len: usize) -> Result<Vec<u8>, ConversionError> {
+    let (tag, body, rest) = read_tlv(der)?;
+    if tag != 0x30 || !rest.is_empty() || scalar_len == 0 {
+        return Err(ConversionError::InvalidEcdsaSignature);
+    }
+    let mut fixed = vec![0; 2 * scalar_len];
+    let mut remaining = body;
+    for half in fixed.chunks_mut(scalar_len) {
+        let (tag, int, rest) = read_tlv(remaining)?;
+        remaining = rest;
+        if tag != 0x02 || int.is_empty() || int[0] & 0x80 != 0 {
+            return Err(ConversionError::InvalidEcdsaSignature);
+        }
+        let int = strip_leading_zeros(int);
+        if int.len() > scalar_len {
+            return Err(ConversionError::InvalidEcdsaSignature);
+        }
+        half[scalar_len - int.len()..].copy_from_slice(int);
+    }
+    if !remaining.is_empty() {
+        return Err(ConversionError::InvalidEcdsaSignature);
+    }
+    Ok(fixed)
+}
+
+/// Converts a JWS `r || s` signature into its DER encoding. The scalar size is implied by the
+/// length of `signature`, which must be even.
+pub fn fixed_to_der(signature: &[u8]) -> Result<Vec<u8>, ConversionError> {
+    if signature.is_empty() || !signature.len().is_multiple_of(2) {
+        return Err(ConversionError::InvalidEcdsaSignature);
+    }
+    let mut body = Vec::with_capacity(signature.len() + 6);
+    for half in signature.chunks(signature.len() / 2) {
+        let mut int = strip_leading_zeros(half);
+        if int.is_empty() {
+            int = &[0];
+        }
+        let pad = int[0] & 0x80 != 0;
+        write_header(&mut body, 0x02, int.len() + pad as usize);
+        if pad {
+            body.push(0);
+        }
+        body.extend_from_slice(int);
+    }
+    let mut der = Vec::with_capacity(body.len() + 3);
+    write_header(&mut der, 0x30, body.len());
+    der.extend_from_slice(&body);
+    Ok(der)
+}
+
+fn strip_leading_zeros(bytes: &[u8]) -> &[u8] {
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+/// Splits a DER tag-length-value having a length of at most two bytes.
+fn read_tlv(der: &[u8]) -> Result<(u8, &[u8], &[u8]), ConversionError> {
+    let invalid = || ConversionError::InvalidEcdsaSignature;
+    let (&tag, rest) = der.split_first().ok_or_else(invalid)?;
+    let (&len, mut rest) = rest.split_first().ok_or_else(invalid)?;
+    let len = match len {
+        0..=0x7f => len as usize,
+        0x81 | 0x82 => {
+            let num_bytes = (len & 0x7f) as usize;
+            if rest.len() < num_bytes {
+                return Err(invalid());
+            }
+            let (len_bytes, after) = rest.split_at(num_bytes);
+            rest = after;
+            len_bytes.iter().fold(0, |len, b| (len << 8) | *b as usize)
+        }
+        _ => return Err(invalid()),
+    };
+    if rest.len() < len {
+        return Err(invalid());
+    }
+    let (value, rest) = rest.split_at(len);
+    Ok((tag, value, rest))
+}
+
+fn write_header(der: &mut Vec<u8>, tag: u8, len: usize) {
+    der.push(tag);
+    match len {
+        0..=0x7f => der.push(len as u8),
+        0x80..=0xff => der.extend_from_slice(&[0x81, len as u8]),
+        _ => der.extend_from_slice(&[0x82, (len >> 8) as u8, len as u8]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut fixed = vec![0u8; 64];
+        fixed[0] = 0x80; // r needs a sign byte
+        fixed[31] = 1;
+        fixed[63] = 0x7f; // s is a single byte
+        let der = fixed_to_der(&fixed).unwrap();
+        assert_eq!(&der[..5], &[0x30, 0x26, 0x02, 0x21, 0x00]);
+        assert_eq!(&der[37..], &[0x02, 0x01, 0x7f]);
+        assert_eq!(der_to_fixed(&der, 32).unwrap(), fixed);
+
+        // P-521 signatures need the long form of the sequence length.
+        let fixed = vec![0xff; 132];
+        let der = fixed_to_der(&fixed).unwrap();
+        assert_eq!(&der[..3], &[0x30, 0x81, 0x8a]);
+        assert_eq!(der_to_fixed(&der, 66).unwrap(), fixed);
+    }
+
+    #[test]
+    fn invalid() {
+        let der = fixed_to_der(&[0x12; 64]).unwrap();
+        assert!(der_to_fixed(&der, 31).is_err());
+        assert!(der_to_fixed(&der[..der.len() - 1], 32).is_err());
+        assert!(der_to_fixed(&[der.as_slice(), &[0]].concat(), 32).is_err());
+        assert!(der_to_fixed(&[0x30, 0x03, 0x02, 0x01, 0x80], 32).is_err()); // negative r
+        assert!(matches!(
+            der_to_fixed(&[0x30, 0], 0),
+            Err(ConversionError::InvalidEcdsaSignature)
+        ));
+        assert!(fixed_to_der(&[1; 63]).is_err());
+        assert!(fixed_to_der(&[]).is_err());
+    }
+}
@@ -0,0 +1,156 @@
+use crate::{Algorithm, Error, JsonWebKey, JsonWebKeySet, Key, KeyOps, KeyUse, KidStrategy};
+
+/// The kind of key to be generated by `JsonWebKey::generate`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeySpec {
+    /// A symmetric key with the specified number of bits.
+    Symmetric(usize),
+    P256,
+    /// Requires the `crypto` feature.
+    #[cfg(feature = "crypto")]
+    P384,
+    Secp256k1,
+    Ed25519,
+    X25519,
+    Ed448,
+}
+
+impl KeySpec {
+    fn generate(self) -> Result<Key, Error> {
+        Ok(match self {
+            Self::Symmetric(num_bits) => Key::try_generate_symmetric(num_bits)?,
+            Self::P256 => Key::generate_p256(),
+            #[cfg(feature = "crypto")]
+            Self::P384 => Key::generate_p384(),
+            Self::Secp256k1 => Key::generate_secp256k1(),
+            Self::Ed25519 => Key::generate_ed25519(),
+            Self::X25519 => Key::generate_x25519(),
+            Self::Ed448 => Key::generate_ed448(),
+        })
+    }
+}
+
+/// Builds a freshly generated `JsonWebKey` with its `use`, `key_ops`, `alg`, and `kid` set.
+/// Created by `JsonWebKey::generate`.
+#[derive(Debug)]
+pub struct JsonWebKeyBuilder {
+    spec: KeySpec,
+    key_use: Option<KeyUse>,
+    key_ops: KeyOps,
+    algorithm: Option<Algorithm>,
+    kid: Option<Kid>,
+}
+
+#[derive(Debug)]
+enum Kid {
+    Fixed(String),
+    Sequential(String),
+    Strategy(KidStrategy),
+}
+
+impl JsonWebKey {
+    /// Returns a builder for a new key of the kind given by `spec`, e.g.
+    /// `JsonWebKey::generate(KeySpec::P256).with_use(KeyUse::Signing)
+    /// .with_algorithm(Algorithm::ES256).with_thumbprint_kid().build()`.
+    pub fn generate(spec: KeySpec) -> JsonWebKeyBuilder {
+        JsonWebKeyBuilder {
+            spec,
+            key_use: None,
+            key_ops: KeyOps::empty(),
+            algorithm: None,
+            kid: None,
+        }
+    }
+}
+
+impl JsonWebKeyBuilder {
+    pub fn with_use(mut self, key_use: KeyUse) -> Self {
+        self.key_use = Some(key_use);
+        self
+    }
+
+    pub fn with_key_ops(mut self, key_ops: KeyOps) -> Self {
+        self.key_ops = key_ops;
+        self
+    }
+
+    pub fn with_algorithm(mut self, alg: Algorithm) -> Self {
+        self.algorithm = Some(alg);
+        self
+    }
+
+    pub fn with_key_id(mut self, kid: impl Into<String>) -> Self {
+        self.kid = Some(Kid::Fixed(kid.into()));
+        self
+    }
+
+    /// Sets the `kid` to the RFC 7638 thumbprint of the generated key.
+    pub fn with_thumbprint_kid(self) -> Self {
+        self.with_kid_strategy(KidStrategy::Thumbprint)
+    }
+
+    /// Sets the `kid` to `prefix` followed by the index of the key: 0 for `build`, and
+    /// 0, 1, 2, ... for the keys generated by `build_set`.
+    pub fn with_sequential_kids(mut self, prefix: impl Into<String>) -> Self {
+        self.kid = Some(Kid::Sequential(prefix.into()));
+        self
+    }
+
+    pub fn with_kid_strategy(mut self, strategy: KidStrategy) -> Self {
+        self.kid = Some(Kid::Strategy(strategy));
+        self
+    }
+
+    /// Generates the key and returns it with the requested members set.
+    /// Returns `Error::MismatchedAlgorithm` if the algorithm can't be used with the kind of key,
+    /// and `Error::InconsistentUsage` if the `use`, `key_ops`, and `alg` contradict each other.
+    pub fn build(self) -> Result<JsonWebKey, Error> {
+        self.validate()?;
+        self.build_key(0)
+    }
+
+    /// Generates `count` keys at once, e.g. to pre-provision a rotation schedule, and returns
+    /// them as a set. The keys are built as by `build`; note that a `kid` set using
+    /// `with_key_id` is shared by all of them, so `with_sequential_kids` or
+    /// `with_thumbprint_kid` are usually preferable.
+    pub fn build_set(self, count: usize) -> Result<JsonWebKeySet, Error> {
+        self.validate()?;
+        (0..count).map(|i| self.build_key(i)).collect()
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        let signing = match (self.key_use, &self.algorithm) {
+            (Some(key_use), Some(alg)) if (key_use == KeyUse::Signing) != alg.is_signing() => {
+                return Err(Error::InconsistentUsage);
+            }
+            (Some(key_use), _) => Some(key_use == KeyUse::Signing),
+            (None, Some(alg)) => Some(alg.is_signing()),
+            (None, None) => None,
+        };
+        let signing_ops = KeyOps::SIGN | KeyOps::VERIFY;
+        let consistent_ops = match signing {
+            Some(true) => signing_ops.contains(self.key_ops),
+            Some(false) => !self.key_ops.intersects(signing_ops),
+            None => true,
+        };
+        if !consistent_ops {
+            return Err(Error::InconsistentUsage);
+        }
+        Ok(())
+    }
+
+    fn build_key(&self, index: usize) -> Result<JsonWebKey, Error> {
+        let mut jwk = JsonWebKey::new(self.spec.generate()?);
+        if let Some(alg) = &self.algorithm {
+            jwk.set_algorithm(alg.clone())?;
+        }
+        jwk.key_use = self.key_use;
+        jwk.key_ops = self.key_ops;
+        jwk.key_id = self.kid.as_ref().map(|kid| match kid {
+            Kid::Fixed(kid) => kid.clone(),
+            Kid::Sequential(prefix) => format!("{}{}", prefix, index),
+            Kid::Strategy(strategy) => strategy.kid_for(&jwk.key),
+        });
+        Ok(jwk)
+    }
+}
@@ -0,0 +1,702 @@
+//! Encryption and decryption of [JWE](https://tools.ietf.org/html/rfc7516) objects in the
+//! compact serialization using `JsonWebKey`s.
+
+use std::num::NonZeroU32;
+
+use ring::{
+    aead, hmac, pbkdf2,
+    rand::{SecureRandom, SystemRandom},
+};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::{
+    utils::{aes, curve25519},
+    Algorithm, ByteArray, ConversionError, Curve, Error, JsonWebKey, Key, OkpCurve,
+};
+
+/// A JWE header. Members other than the registered ones below are kept in `other`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Header {
+    /// The key management algorithm.
+    pub alg: Algorithm,
+
+    /// The content encryption algorithm.
+    pub enc: Algorithm,
+
+    /// The ephemeral public key of the ECDH-ES algorithms.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epk: Option<JsonWebKey>,
+
+    /// The base64url-encoded salt input of the PBES2 algorithms.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub p2s: Option<String>,
+
+    /// The PBKDF2 iteration count of the PBES2 algorithms.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub p2c: Option<u32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kid: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub typ: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cty: Option<String>,
+
+    /// Names of members which must be understood by recipients. As no extensions are
+    /// supported, objects having this member are rejected when decrypted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crit: Option<Vec<String>>,
+
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Header {
+    pub fn new(alg: Algorithm, enc: Algorithm) -> Self {
+        Self {
+            alg,
+            enc,
+            epk: None,
+            p2s: None,
+            p2c: None,
+            kid: None,
+            typ: None,
+            cty: None,
+            crit: None,
+            other: Default::default(),
+        }
+    }
+
+    pub fn with_kid(mut self, kid: impl Into<String>) -> Self {
+        self.kid = Some(kid.into());
+        self
+    }
+
+    pub fn with_typ(mut self, typ: impl Into<String>) -> Self {
+        self.typ = Some(typ.into());
+        self
+    }
+
+    pub fn with_cty(mut self, cty: impl Into<String>) -> Self {
+        self.cty = Some(cty.into());
+        self
+    }
+}
+
+/// The number of PBKDF2 iterations used by `JsonWebKey::seal_with_password`, as recommended by
+/// OWASP for PBKDF2-HMAC-SHA256.
+const PBES2_ITERATIONS: u32 = 600_000;
+
+/// The largest PBKDF2 iteration count (`p2c`) accepted. As the count is chosen by the sender and
+/// the key is derived before the authentication tag can be checked, a larger one would let any
+/// token cost its recipient an arbitrary amount of CPU time.
+pub const PBES2_MAX_ITERATIONS: u32 = 1_000_000;
+
+/// The smallest PBES2 salt input (`p2s`) accepted, in bytes
+/// ([RFC 7518 §4.8.1.1](https://tools.ietf.org/html/rfc7518#section-4.8.1.1)).
+const PBES2_MIN_SALT_LEN: usize = 8;
+
+impl JsonWebKey {
+    /// Encrypts `plaintext` to this key using the key management algorithm of its `alg` and
+    /// the content encryption algorithm `enc`, and returns the compact serialization.
+    /// Returns `Error::MissingAlgorithm` if this key has no `alg`.
+    ///
+    /// The supported key management algorithms are `dir`, A128KW, A192KW, A256KW, PBES2-HS*+A*KW
+    /// (the password being the value of a symmetric key), and (with P-256 and X25519 keys)
+    /// ECDH-ES and ECDH-ES+A*KW. The supported content encryption algorithms are A128GCM,
+    /// A256GCM, A128CBC-HS256, A192CBC-HS384, and A256CBC-HS512.
+    pub fn encrypt_jwe(&self, plaintext: &[u8], enc: Algorithm) -> Result<String, Error> {
+        let alg = self.algorithm.clone().ok_or(Error::MissingAlgorithm)?;
+        let mut header = Header::new(alg, enc);
+        header.kid = self.key_id.clone();
+        self.encrypt_jwe_with_header(plaintext, header)
+    }
+
+    /// Like `encrypt_jwe`, but using the algorithms and other members of `header`, whose `alg`
+    /// must agree with that of this key, if set. The `epk` and `p2s` members are set by the key
+    /// management algorithm, and a missing or zero `p2c` is set to 600,000 PBKDF2 iterations.
+    /// Returns `Error::InvalidJwe` if `p2c` exceeds `PBES2_MAX_ITERATIONS`.
+    pub fn encrypt_jwe_with_header(
+        &self,
+        plaintext: &[u8],
+        mut header: Header,
+    ) -> Result<String, Error> {
+        let alg = header.alg.clone();
+        let enc = header.enc.clone();
+        if matches!(&self.algorithm, Some(key_alg) if key_alg != &alg) {
+            return Err(Error::MismatchedAlgorithm);
+        }
+        JsonWebKey::validate_algorithm(&alg, &self.key)?;
+        let cek_len = enc.content_key_len().ok_or(Error::MismatchedAlgorithm)?;
+        let rng = SystemRandom::new();
+        header.epk = None;
+        header.p2s = None;
+
+        let (cek, encrypted_key) = match &alg {
+            Algorithm::Dir => (direct_key(&self.key, cek_len)?, Vec::new()),
+            Algorithm::A128KW | Algorithm::A192KW | Algorithm::A256KW => {
+                let cek = random(&rng, cek_len)?;
+                let encrypted_key = self.key.wrap_key(&alg, &cek)?;
+                (cek, encrypted_key)
+            }
+            Algorithm::EcdhEs
+            | Algorithm::EcdhEsA128KW
+            | Algorithm::EcdhEsA192KW
+            | Algorithm::EcdhEsA256KW => {
+                let ephemeral = generate_ephemeral(&self.key, &rng)?;
+                header.epk = Some(JsonWebKey::new(ephemeral.to_public().unwrap().into_owned()));
+                if alg == Algorithm::EcdhEs {
+                    let agreed = ephemeral.derive_shared_secret(&self.key, &enc, &[], &[])?;
+                    (direct_key(&agreed, cek_len)?, Vec::new())
+                } else {
+                    let agreed = ephemeral.derive_shared_secret(&self.key, &alg, &[], &[])?;
+                    let cek = random(&rng, cek_len)?;
+                    let encrypted_key = agreed.wrap_key(&key_wrap_alg(&alg), &cek)?;
+                    (cek, encrypted_key)
+                }
+            }
+            Algorithm::Pbes2Hs256A128KW
+            | Algorithm::Pbes2Hs384A192KW
+            | Algorithm::Pbes2Hs512A256KW => {
+                let p2s = random(&rng, 16)?;
+                let p2c = match header.p2c {
+                    Some(p2c) if p2c > 0 => p2c,
+                    _ => PBES2_ITERATIONS,
+                };
+                header.p2s = Some(encode(&p2s));
+                header.p2c = Some(p2c);
+                let wrapping_key = pbes2_key(&alg, &self.key, &p2s, p2c)?;
+                let cek = random(&rng, cek_len)?;
+                let encrypted_key = wrapping_key.wrap_key(&key_wrap_alg(&alg), &cek)?;
+                (cek, encrypted_key)
+            }
+            _ => return Err(ConversionError::UnsupportedKey.into()),
+        };
+
+        let protected = encode(&serde_json::to_vec(&header)?);
+        let (iv, ciphertext, tag) =
+            encrypt_content(&enc, &cek, protected.as_bytes(), plaintext, &rng)?;
+        Ok([
+            protected,
+            encode(&encrypted_key),
+            encode(&iv),
+            encode(&ciphertext),
+            encode(&tag),
+        ]
+        .join("."))
+    }
+
+    /// Decrypts the compact serialization `token` with this key and returns its header and
+    /// plaintext. The `alg` of the header must agree with that of the key, if set.
+    /// Returns `Error::DecryptionFailed` if the token was not encrypted to this key or has been
+    /// altered, and `Error::InvalidJwe` if it uses PBES2 with a `p2c` above
+    /// `PBES2_MAX_ITERATIONS` or a `p2s` shorter than 8 bytes.
+    pub fn decrypt_jwe(&self, token: &str) -> Result<(Header, Vec<u8>), Error> {
+        let parts: Vec<&str> = token.split('.').collect();
+        let (protected, encrypted_key, iv, ciphertext, tag) = match parts.as_slice() {
+            [protected, encrypted_key, iv, ciphertext, tag] => (
+                *protected,
+                decode(encrypted_key)?,
+                decode(iv)?,
+                decode(ciphertext)?,
+                decode(tag)?,
+            ),
+            _ => return Err(Error::InvalidJwe),
+        };
+        let header: Header = serde_json::from_slice(&decode(protected)?)?;
+        if header.crit.is_some() || header.other.contains_key("zip") {
+            return Err(Error::InvalidJwe);
+        }
+        if matches!(&self.algorithm, Some(alg) if alg != &header.alg) {
+            return Err(Error::MismatchedAlgorithm);
+        }
+        JsonWebKey::validate_algorithm(&header.alg, &self.key)?;
+        let cek_len = header.enc.content_key_len().ok_or(Error::InvalidJwe)?;
+
+        let cek = match &header.alg {
+            Algorithm::Dir if encrypted_key.is_empty() => direct_key(&self.key, cek_len)?,
+            Algorithm::A128KW | Algorithm::A192KW | Algorithm::A256KW => {
+                Zeroizing::new(self.key.unwrap_key(&header.alg, &encrypted_key)?)
+            }
+            Algorithm::EcdhEs if encrypted_key.is_empty() => {
+                let epk = header.epk.as_ref().ok_or(Error::InvalidJwe)?;
+                let agreed = self
+                    .key
+                    .derive_shared_secret(&epk.key, &header.enc, &[], &[])?;
+                direct_key(&agreed, cek_len)?
+            }
+            Algorithm::EcdhEsA128KW | Algorithm::EcdhEsA192KW | Algorithm::EcdhEsA256KW => {
+                let epk = header.epk.as_ref().ok_or(Error::InvalidJwe)?;
+                let agreed = self
+                    .key
+                    .derive_shared_secret(&epk.key, &header.alg, &[], &[])?;
+                Zeroizing::new(agreed.unwrap_key(&key_wrap_alg(&header.alg), &encrypted_key)?)
+            }
+            Algorithm::Pbes2Hs256A128KW
+            | Algorithm::Pbes2Hs384A192KW
+            | Algorithm::Pbes2Hs512A256KW => {
+                let p2s = decode(header.p2s.as_deref().ok_or(Error::InvalidJwe)?)?;
+                let p2c = header.p2c.ok_or(Error::InvalidJwe)?;
+                let wrapping_key = pbes2_key(&header.alg, &self.key, &p2s, p2c)?;
+                Zeroizing::new(wrapping_key.unwrap_key(&key_wrap_alg(&header.alg), &encrypted_key)?)
+            }
+            Algorithm::Dir | Algorithm::EcdhEs => return Err(Error::InvalidJwe),
+            _ => return Err(ConversionError::UnsupportedKey.into()),
+        };
+        if cek.len() != cek_len {
+            return Err(Error::DecryptionFailed);
+        }
+
+        let plaintext = decrypt_content(
+            &header.enc,
+            &cek,
+            protected.as_bytes(),
+            &iv,
+            &ciphertext,
+            &tag,
+        )?;
+        Ok((header, plaintext))
+    }
+
+    /// Encrypts this key to `password` using PBES2-HS256+A128KW and A128CBC-HS256, as
+    /// recommended by [RFC 7517 §7](https://tools.ietf.org/html/rfc7517#section-7), and
+    /// returns the compact serialization. The private parts of the key are included.
+    pub fn seal_with_password(&self, password: impl AsRef<[u8]>) -> Result<String, Error> {
+        let mut wrapping_key = JsonWebKey::new(Key::Symmetric {
+            key: password.as_ref().to_vec().into(),
+        });
+        wrapping_key.algorithm = Some(Algorithm::Pbes2Hs256A128KW);
+        let header =
+            Header::new(Algorithm::Pbes2Hs256A128KW, Algorithm::A128CbcHs256).with_cty("jwk+json");
+        let plaintext = Zeroizing::new(serde_json::to_vec(self)?);
+        wrapping_key.encrypt_jwe_with_header(&plaintext, header)
+    }
+
+    /// Decrypts a key sealed by `seal_with_password`, or by any other implementation of the
+    /// PBES2 key management algorithms. Returns `Error::DecryptionFailed` if `password` is
+    /// wrong, and `Error::InvalidJwe` without deriving any key if the `p2c` of `token` exceeds
+    /// `PBES2_MAX_ITERATIONS`, so that untrusted blobs can't make this hang.
+    pub fn unseal_with_password(token: &str, password: impl AsRef<[u8]>) -> Result<Self, Error> {
+        let wrapping_key = JsonWebKey::new(Key::Symmetric {
+            key: password.as_ref().to_vec().into(),
+        });
+        let (header, plaintext) = wrapping_key.decrypt_jwe(token)?;
+        if !matches!(
+            header.alg,
+            Algorithm::Pbes2Hs256A128KW | Algorithm::Pbes2Hs384A192KW | Algorithm::Pbes2Hs512A256KW
+        ) {
+            return Err(Error::MismatchedAlgorithm);
+        }
+        let plaintext = Zeroizing::new(plaintext);
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+}
+
+/// Derives the key wrapping key of a PBES2 algorithm from the password `key`
+/// ([RFC 7518 §4.8.1.1](https://tools.ietf.org/html/rfc7518#section-4.8.1.1)), after checking
+/// `p2s` and `p2c` against the limits above.
+fn pbes2_key(alg: &Algorithm, key: &Key, p2s: &[u8], p2c: u32) -> Result<Key, Error> {
+    let (prf, key_len) = match alg {
+        Algorithm::Pbes2Hs256A128KW => (pbkdf2::PBKDF2_HMAC_SHA256, 16),
+        Algorithm::Pbes2Hs384A192KW => (pbkdf2::PBKDF2_HMAC_SHA384, 24),
+        _ => (pbkdf2::PBKDF2_HMAC_SHA512, 32),
+    };
+    let password = match key {
+        Key::Symmetric { key } => key,
+        _ => return Err(Error::MismatchedAlgorithm),
+    };
+    if p2s.len() < PBES2_MIN_SALT_LEN || p2c > PBES2_MAX_ITERATIONS {
+        return Err(Error::InvalidJwe);
+    }
+    let iterations = NonZeroU32::new(p2c).ok_or(Error::InvalidJwe)?;
+    let salt = [alg.to_string().as_bytes(), &[0], p2s].concat();
+    let mut derived = vec![0; key_len];
+    pbkdf2::derive(prf, iterations, &salt, password, &mut derived);
+    Ok(Key::Symmetric {
+        key: derived.into(),
+    })
+}
+
+/// Returns the value of the symmetric `key`, which must be `len` bytes long.
+fn direct_key(key: &Key, len: usize) -> Result<Zeroizing<Vec<u8>>, Error> {
+    match key {
+        Key::Symmetric { key } if key.len() == len => Ok(Zeroizing::new(key.to_vec())),
+        _ => Err(Error::MismatchedAlgorithm),
+    }
+}
+
+/// Returns the AES key wrap algorithm of an ECDH-ES+A*KW or PBES2 algorithm.
+fn key_wrap_alg(alg: &Algorithm) -> Algorithm {
+    match alg {
+        Algorithm::EcdhEsA128KW | Algorithm::Pbes2Hs256A128KW => Algorithm::A128KW,
+        Algorithm::EcdhEsA192KW | Algorithm::Pbes2Hs384A192KW => Algorithm::A192KW,
+        _ => Algorithm::A256KW,
+    }
+}
+
+/// Generates an ephemeral private key on the curve of the public key `recipient`.
+fn generate_ephemeral(recipient: &Key, rng: &SystemRandom) -> Result<Key, Error> {
+    match recipient {
+        Key::EC {
+            curve: Curve::P256 { .. },
+        } => {
+            use ring::signature::{EcdsaKeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+
+            let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, rng)
+                .map_err(|_| ConversionError::UnsupportedKey)?;
+            Ok(Key::from_pkcs8_der(pkcs8.as_ref())?)
+        }
+        Key::OKP {
+            curve: OkpCurve::X25519 { .. },
+        } => {
+            let mut d = Zeroizing::new([0; 32]);
+            rng.fill(&mut *d)
+                .map_err(|_| ConversionError::UnsupportedKey)?;
+            let x = curve25519::x25519_public(&d);
+            Ok(Key::OKP {
+                curve: OkpCurve::X25519 {
+                    d: Some(ByteArray::try_from_slice(*d).unwrap()),
+                    x: ByteArray::try_from_slice(x).unwrap(),
+                },
+            })
+        }
+        _ => Err(ConversionError::UnsupportedKey.into()),
+    }
+}
+
+fn random(rng: &SystemRandom, len: usize) -> Result<Zeroizing<Vec<u8>>, Error> {
+    let mut bytes = Zeroizing::new(vec![0; len]);
+    rng.fill(&mut bytes)
+        .map_err(|_| ConversionError::UnsupportedKey)?;
+    Ok(bytes)
+}
+
+fn gcm_key(enc: &Algorithm, cek: &[u8]) -> Result<aead::LessSafeKey, Error> {
+    let algorithm = match enc {
+        Algorithm::A128GCM => &aead::AES_128_GCM,
+        _ => &aead::AES_256_GCM,
+    };
+    let key = aead::UnboundKey::new(algorithm, cek).map_err(|_| Error::MismatchedAlgorithm)?;
+    Ok(aead::LessSafeKey::new(key))
+}
+
+/// Returns the IV, ciphertext, and authentication tag of `plaintext`.
+#[allow(clippy::type_complexity)]
+fn encrypt_content(
+    enc: &Algorithm,
+    cek: &[u8],
+    aad: &[u8],
+    plaintext: &[u8],
+    rng: &SystemRandom,
+) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>), Error> {
+    match enc {
+        Algorithm::A128GCM | Algorithm::A256GCM => {
+            let mut iv = [0; aead::NONCE_LEN];
+            rng.fill(&mut iv)
+                .map_err(|_| ConversionError::UnsupportedKey)?;
+            let mut ciphertext = plaintext.to_vec();
+            let tag = gcm_key(enc, cek)?
+                .seal_in_place_separate_tag(
+                    aead::Nonce::assume_unique_for_key(iv),
+                    aead::Aad::from(aad),
+                    &mut ciphertext,
+                )
+                .map_err(|_| ConversionError::UnsupportedKey)?;
+            Ok((iv.to_vec(), ciphertext, tag.as_ref().to_vec()))
+        }
+        Algorithm::A128CbcHs256 | Algorithm::A192CbcHs384 | Algorithm::A256CbcHs512 => {
+            let (mac_key, enc_key) = cek.split_at(cek.len() / 2);
+            let mut iv = [0; aes::BLOCK_LEN];
+            rng.fill(&mut iv)
+                .map_err(|_| ConversionError::UnsupportedKey)?;
+            let ciphertext = aes::cbc_encrypt(&aes::Aes::new(enc_key).unwrap(), &iv, plaintext);
+            let tag = cbc_hmac_tag(enc, mac_key, aad, &iv, &ciphertext);
+            Ok((iv.to_vec(), ciphertext, tag))
+        }
+        _ => Err(ConversionError::UnsupportedKey.into()),
+    }
+}
+
+fn decrypt_content(
+    enc: &Algorithm,
+    cek: &[u8],
+    aad: &[u8],
+    iv: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8],
+) -> Result<Vec<u8>, Error> {
+    match enc {
+        Algorithm::A128GCM | Algorithm::A256GCM => {
+            let nonce =
+                aead::Nonce::try_assume_unique_for_key(iv).map_err(|_| Error::DecryptionFailed)?;
+            let mut in_out = Zeroizing::new([ciphertext, tag].concat());
+            let plaintext = gcm_key(enc, cek)?
+                .open_in_place(nonce, aead::Aad::from(aad), &mut in_out)
+                .map_err(|_| Error::DecryptionFailed)?;
+            Ok(plaintext.to_vec())
+        }
+        Algorithm::A128CbcHs256 | Algorithm::A192CbcHs384 | Algorithm::A256CbcHs512 => {
+            let (mac_key, enc_key) = cek.split_at(cek.len() / 2);
+            let expected_tag = cbc_hmac_tag(enc, mac_key, aad, iv, ciphertext);
+            ring::constant_time::verify_slices_are_equal(&expected_tag, tag)
+                .map_err(|_| Error::DecryptionFailed)?;
+            let mut block_iv = [0; aes::BLOCK_LEN];
+            if iv.len() != block_iv.len() {
+                return Err(Error::DecryptionFailed);
+            }
+            block_iv.copy_from_slice(iv);
+            let plaintext =
+                aes::cbc_decrypt(&aes::Aes::new(enc_key).unwrap(), &block_iv, ciphertext)
+                    .ok_or(Error::DecryptionFailed)?;
+            Ok(plaintext.to_vec())
+        }
+        _ => Err(ConversionError::UnsupportedKey.into()),
+    }
+}
+
+/// The authentication tag of the AES-CBC-HMAC-SHA2 algorithms
+/// ([RFC 7518 §5.2.2.1](https://tools.ietf.org/html/rfc7518#section-5.2.2.1)).
+fn cbc_hmac_tag(
+    enc: &Algorithm,
+    mac_key: &[u8],
+    aad: &[u8],
+    iv: &[u8],
+    ciphertext: &[u8],
+) -> Vec<u8> {
+    let algorithm = match enc {
+        Algorithm::A128CbcHs256 => hmac::HMAC_SHA256,
+        Algorithm::A192CbcHs384 => hmac::HMAC_SHA384,
+        _ => hmac::HMAC_SHA512,
+    };
+    let mut context = hmac::Context::with_key(&hmac::Key::new(algorithm, mac_key));
+    context.update(aad);
+    context.update(iv);
+    context.update(ciphertext);
+    context.update(&(8 * aad.len() as u64).to_be_bytes());
+    let mut tag = context.sign().as_ref().to_vec();
+    tag.truncate(mac_key.len());
+    tag
+}
+
+fn encode(bytes: &[u8]) -> String {
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
+fn decode(b64: &str) -> Result<Vec<u8>, Error> {
+    Ok(base64::decode_config(b64, base64::URL_SAFE_NO_PAD)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 7516 appendix A.3
+    static A128KW_JWK: &str = r#"{"kty": "oct", "k": "GawgguFyGrWKav7AX4VKUg"}"#;
+    static A128KW_JWE: &str = "eyJhbGciOiJBMTI4S1ciLCJlbmMiOiJBMTI4Q0JDLUhTMjU2In0.\
+        6KB707dM9YTIgHtLvtgWQ8mKwboJW3of9locizkDTHzBC2IlrT1oOQ.\
+        AxY8DCtDaGlsbGljb3RoZQ.\
+        KDlTtXchhZTGufMYmOYGS4HffxPSUrfmqCHXaI9wOGY.\
+        U0m_YmjN04DJvceFICbCVQ";
+
+    #[test]
+    fn decrypt_rfc7516() {
+        let jwk: JsonWebKey = A128KW_JWK.parse().unwrap();
+        let (header, plaintext) = jwk.decrypt_jwe(A128KW_JWE).unwrap();
+        assert_eq!(header.alg, Algorithm::A128KW);
+        assert_eq!(header.enc, Algorithm::A128CbcHs256);
+        assert_eq!(plaintext, b"Live long and prosper.");
+
+        let tampered = A128KW_JWE.replace(".KDlT", ".KDlU");
+        assert!(matches!(
+            jwk.decrypt_jwe(&tampered),
+            Err(Error::DecryptionFailed)
+        ));
+        assert!(matches!(
+            jwk.decrypt_jwe(&A128KW_JWE[..A128KW_JWE.rfind('.').unwrap()]),
+            Err(Error::InvalidJwe)
+        ));
+    }
+
+    #[test]
+    fn encrypt_decrypt() {
+        let p256: JsonWebKey = r#"{
+            "kty": "EC",
+            "crv": "P-256",
+            "d": "ZoKQ9j4dhIBlMRVrv-QG8P_T9sutv3_95eio9MtpgKg",
+            "x": "QOMHmv96tVlJv-uNqprnDSKIj5AiLTXKRomXYnav0N0",
+            "y": "TjYZoHnctatEE6NCrKmXQdJJPnNzZEX8nBmZde3AY4k"
+        }"#
+        .parse()
+        .unwrap();
+        let x25519: JsonWebKey = r#"{
+            "kty": "OKP",
+            "crv": "X25519",
+            "d": "dwdtCnMYpX08FsFyUbJmRd9ML4frwJkqsXf7pR25LCo",
+            "x": "hSDwCYkwp1R0i33ctD73Wg2_Og0mOBr066SpjqqbTmo"
+        }"#
+        .parse()
+        .unwrap();
+        let oct = |len: usize| {
+            JsonWebKey::new(Key::Symmetric {
+                key: vec![7; len].into(),
+            })
+        };
+        let cases = [
+            (oct(32), Algorithm::Dir, Algorithm::A128CbcHs256),
+            (oct(16), Algorithm::A128KW, Algorithm::A128GCM),
+            (oct(24), Algorithm::A192KW, Algorithm::A192CbcHs384),
+            (oct(32), Algorithm::A256KW, Algorithm::A256CbcHs512),
+            (p256.clone(), Algorithm::EcdhEs, Algorithm::A256GCM),
+            (p256, Algorithm::EcdhEsA128KW, Algorithm::A128CbcHs256),
+            (x25519.clone(), Algorithm::EcdhEs, Algorithm::A128GCM),
+            (x25519, Algorithm::EcdhEsA256KW, Algorithm::A256GCM),
+        ];
+        for (mut jwk, alg, enc) in cases.iter().cloned() {
+            assert!(matches!(
+                jwk.encrypt_jwe(b"jsonwebkey", enc.clone()),
+                Err(Error::MissingAlgorithm)
+            ));
+            jwk.set_algorithm(alg.clone()).unwrap();
+            jwk.key_id = Some("recipient".into());
+            let recipient = match jwk.key.to_public() {
+                Some(public) => JsonWebKey {
+                    key: Box::new(public.into_owned()),
+                    ..jwk.clone()
+                },
+                None => jwk.clone(),
+            };
+            let token = recipient.encrypt_jwe(b"jsonwebkey", enc.clone()).unwrap();
+            let (header, plaintext) = jwk.decrypt_jwe(&token).unwrap();
+            assert_eq!((header.alg, header.enc), (alg.clone(), enc));
+            assert_eq!(header.kid.as_deref(), Some("recipient"));
+            assert_eq!(
+                header.epk.is_some(),
+                matches!(
+                    alg,
+                    Algorithm::EcdhEs | Algorithm::EcdhEsA128KW | Algorithm::EcdhEsA256KW
+                )
+            );
+            assert_eq!(plaintext, b"jsonwebkey");
+        }
+
+        let mut dir = oct(16);
+        dir.set_algorithm(Algorithm::Dir).unwrap();
+        assert!(matches!(
+            dir.encrypt_jwe(b"jsonwebkey", Algorithm::A256GCM),
+            Err(Error::MismatchedAlgorithm)
+        ));
+    }
+
+    #[test]
+    fn pbes2() {
+        let mut password = JsonWebKey::new(Key::Symmetric {
+            key: b"correct horse".to_vec().into(),
+        });
+        password.set_algorithm(Algorithm::Pbes2Hs384A192KW).unwrap();
+        let mut header = Header::new(Algorithm::Pbes2Hs384A192KW, Algorithm::A192CbcHs384);
+        header.p2c = Some(1000);
+        let token = password
+            .encrypt_jwe_with_header(b"jsonwebkey", header)
+            .unwrap();
+        let (header, plaintext) = password.decrypt_jwe(&token).unwrap();
+        assert_eq!(header.p2c, Some(1000));
+        assert_eq!(decode(header.p2s.as_deref().unwrap()).unwrap().len(), 16);
+        assert_eq!(plaintext, b"jsonwebkey");
+
+        let wrong = JsonWebKey::new(Key::Symmetric {
+            key: b"battery staple".to_vec().into(),
+        });
+        assert!(matches!(
+            wrong.decrypt_jwe(&token),
+            Err(Error::DecryptionFailed)
+        ));
+
+        let mut header = Header::new(Algorithm::Pbes2Hs384A192KW, Algorithm::A192CbcHs384);
+        header.p2c = Some(PBES2_MAX_ITERATIONS + 1);
+        assert!(matches!(
+            password.encrypt_jwe_with_header(b"jsonwebkey", header),
+            Err(Error::InvalidJwe)
+        ));
+    }
+
+    /// Replaces the `p2s` and `p2c` members of the protected header of `token`.
+    fn with_pbes2_params(token: &str, p2s: &[u8], p2c: u32) -> String {
+        let (protected, rest) = token.split_at(token.find('.').unwrap());
+        let mut header: Header = serde_json::from_slice(&decode(protected).unwrap()).unwrap();
+        header.p2s = Some(encode(p2s));
+        header.p2c = Some(p2c);
+        encode(&serde_json::to_vec(&header).unwrap()) + rest
+    }
+
+    #[test]
+    fn pbes2_limits() {
+        let mut password = JsonWebKey::new(Key::Symmetric {
+            key: b"correct horse".to_vec().into(),
+        });
+        password.set_algorithm(Algorithm::Pbes2Hs256A128KW).unwrap();
+        let mut header = Header::new(Algorithm::Pbes2Hs256A128KW, Algorithm::A128GCM);
+        header.p2c = Some(1000);
+        let token = password
+            .encrypt_jwe_with_header(b"jsonwebkey", header)
+            .unwrap();
+
+        let start = std::time::Instant::now();
+        let expensive = with_pbes2_params(&token, &[0; 16], u32::MAX);
+        assert!(matches!(
+            password.decrypt_jwe(&expensive),
+            Err(Error::InvalidJwe)
+        ));
+        assert!(start.elapsed() < std::time::Duration::from_secs(1));
+
+        let short_salt = with_pbes2_params(&token, &[0; 7], 1000);
+        assert!(matches!(
+            password.decrypt_jwe(&short_salt),
+            Err(Error::InvalidJwe)
+        ));
+        let zero_count = with_pbes2_params(&token, &[0; 16], 0);
+        assert!(matches!(
+            password.decrypt_jwe(&zero_count),
+            Err(Error::InvalidJwe)
+        ));
+    }
+
+    #[test]
+    fn seal_with_password() {
+        let jwk: JsonWebKey = r#"{
+            "kty": "OKP",
+            "crv": "X25519",
+            "d": "dwdtCnMYpX08FsFyUbJmRd9ML4frwJkqsXf7pR25LCo",
+            "x": "hSDwCYkwp1R0i33ctD73Wg2_Og0mOBr066SpjqqbTmo",
+            "kid": "sealed"
+        }"#
+        .parse()
+        .unwrap();
+        let token = jwk.seal_with_password("hunter2").unwrap();
+        let header: Header =
+            serde_json::from_slice(&decode(token.split('.').next().unwrap()).unwrap()).unwrap();
+        assert_eq!(header.alg, Algorithm::Pbes2Hs256A128KW);
+        assert_eq!(header.enc, Algorithm::A128CbcHs256);
+        assert_eq!(header.cty.as_deref(), Some("jwk+json"));
+        assert_eq!(header.p2c, Some(PBES2_ITERATIONS));
+
+        assert_eq!(
+            JsonWebKey::unseal_with_password(&token, "hunter2").unwrap(),
+            jwk
+        );
+        assert!(matches!(
+            JsonWebKey::unseal_with_password(&token, "hunter3"),
+            Err(Error::DecryptionFailed)
+        ));
+
+        let start = std::time::Instant::now();
+        let expensive = with_pbes2_params(&token, &[0; 16], u32::MAX);
+        assert!(matches!(
+            JsonWebKey::unseal_with_password(&expensive, "hunter2"),
+            Err(Error::InvalidJwe)
+        ));
+        assert!(start.elapsed() < std::time::Duration::from_secs(1));
+    }
+}
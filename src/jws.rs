@@ -0,0 +1,469 @@
+//! Signing and verification of [JWS](https://tools.ietf.org/html/rfc7515) objects using
+//! `JsonWebKey`s, without depending on a JWT library.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Algorithm, Error, JsonWebKey};
+
+/// A JOSE header. Members other than the registered ones below are kept in `other`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Header {
+    pub alg: Algorithm,
+
+    /// The public key used to sign, as in the requests of ACME accounts being created.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jwk: Option<JsonWebKey>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kid: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub typ: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cty: Option<String>,
+
+    /// Names of members which must be understood by verifiers. As no extensions are supported,
+    /// objects having headers with this member are rejected when verified.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crit: Option<Vec<String>>,
+
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Header {
+    pub fn new(alg: Algorithm) -> Self {
+        Self {
+            alg,
+            jwk: None,
+            kid: None,
+            typ: None,
+            cty: None,
+            crit: None,
+            other: Default::default(),
+        }
+    }
+
+    /// Returns a header having the `alg` and `kid` of `jwk`, or
+    /// `Error::MissingAlgorithm` if the key has no `alg`.
+    pub fn for_key(jwk: &JsonWebKey) -> Result<Self, Error> {
+        let alg = jwk.algorithm.clone().ok_or(Error::MissingAlgorithm)?;
+        Ok(Self {
+            kid: jwk.key_id.clone(),
+            ..Self::new(alg)
+        })
+    }
+
+    pub fn with_kid(mut self, kid: impl Into<String>) -> Self {
+        self.kid = Some(kid.into());
+        self
+    }
+
+    pub fn with_typ(mut self, typ: impl Into<String>) -> Self {
+        self.typ = Some(typ.into());
+        self
+    }
+
+    pub fn with_cty(mut self, cty: impl Into<String>) -> Self {
+        self.cty = Some(cty.into());
+        self
+    }
+}
+
+/// Signs `payload` with `jwk` and returns the compact serialization
+/// `header.payload.signature`. The `alg` of `header` must agree with that of the key, if set.
+pub fn sign_compact(jwk: &JsonWebKey, header: &Header, payload: &[u8]) -> Result<String, Error> {
+    let protected = encode(&serde_json::to_vec(header)?);
+    let signing_input = format!("{}.{}", protected, encode(payload));
+    let signature = sign(jwk, &header.alg, signing_input.as_bytes())?;
+    Ok(format!("{}.{}", signing_input, encode(&signature)))
+}
+
+/// Verifies the compact serialization `token` with `jwk` and returns its header and payload.
+/// The `alg` of the header must agree with that of the key, if set.
+pub fn verify_compact(jwk: &JsonWebKey, token: &str) -> Result<(Header, Vec<u8>), Error> {
+    let mut parts = token.split('.');
+    let (protected, payload, signature) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(protected), Some(payload), Some(signature)) if parts.next().is_none() => {
+            (protected, payload, signature)
+        }
+        _ => return Err(Error::InvalidJws),
+    };
+    let header = decode_header(Some(protected), None)?;
+    let signing_input = &token[..protected.len() + 1 + payload.len()];
+    verify(
+        jwk,
+        &header.alg,
+        signing_input.as_bytes(),
+        &decode(signature)?,
+    )?;
+    Ok((header, decode(payload)?))
+}
+
+/// Returns the header of the compact serialization `token` without verifying its signature,
+/// e.g. to select the verification key by `kid`.
+pub fn decode_compact_header(token: &str) -> Result<Header, Error> {
+    match token.split('.').next() {
+        Some(protected) if token.len() > protected.len() => decode_header(Some(protected), None),
+        _ => Err(Error::InvalidJws),
+    }
+}
+
+/// A JWS in the flattened JSON serialization, having a single signature
+/// ([RFC 7515 §7.2.2](https://tools.ietf.org/html/rfc7515#section-7.2.2)).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlattenedJws {
+    /// The base64url-encoded payload.
+    pub payload: String,
+    #[serde(flatten)]
+    pub signature: JwsSignature,
+}
+
+/// A JWS in the general JSON serialization, having any number of signatures
+/// ([RFC 7515 §7.2.1](https://tools.ietf.org/html/rfc7515#section-7.2.1)).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeneralJws {
+    /// The base64url-encoded payload.
+    pub payload: String,
+    pub signatures: Vec<JwsSignature>,
+}
+
+/// A signature of a JWS in a JSON serialization, together with its headers.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JwsSignature {
+    /// The base64url-encoded protected header.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protected: Option<String>,
+    /// The unprotected header, whose members must not also be in the protected header.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<serde_json::Map<String, serde_json::Value>>,
+    /// The base64url-encoded signature.
+    pub signature: String,
+}
+
+impl JwsSignature {
+    fn sign(
+        jwk: &JsonWebKey,
+        protected: &Header,
+        unprotected: Option<serde_json::Map<String, serde_json::Value>>,
+        payload: &str,
+    ) -> Result<Self, Error> {
+        let header = serde_json::to_value(protected)?;
+        let duplicate = |name: &String| header.get(name).is_some();
+        if unprotected.iter().flat_map(|u| u.keys()).any(duplicate) {
+            return Err(Error::InvalidJws);
+        }
+        let alg = &protected.alg;
+        let protected = encode(&serde_json::to_vec(protected)?);
+        let signing_input = format!("{}.{}", protected, payload);
+        let signature = sign(jwk, alg, signing_input.as_bytes())?;
+        Ok(Self {
+            protected: Some(protected),
+            header: unprotected,
+            signature: encode(&signature),
+        })
+    }
+
+    /// Verifies this signature of `payload` with `jwk` and returns the union of its headers.
+    fn verify(&self, jwk: &JsonWebKey, payload: &str) -> Result<Header, Error> {
+        let header = decode_header(self.protected.as_deref(), self.header.as_ref())?;
+        let signing_input = format!("{}.{}", self.protected.as_deref().unwrap_or(""), payload);
+        verify(
+            jwk,
+            &header.alg,
+            signing_input.as_bytes(),
+            &decode(&self.signature)?,
+        )?;
+        Ok(header)
+    }
+}
+
+impl FlattenedJws {
+    /// Signs `payload` with `jwk`, as by `sign_compact`, additionally attaching the
+    /// `unprotected` header members, if any.
+    pub fn sign(
+        jwk: &JsonWebKey,
+        protected: &Header,
+        unprotected: Option<serde_json::Map<String, serde_json::Value>>,
+        payload: &[u8],
+    ) -> Result<Self, Error> {
+        let payload = encode(payload);
+        Ok(Self {
+            signature: JwsSignature::sign(jwk, protected, unprotected, &payload)?,
+            payload,
+        })
+    }
+
+    /// Verifies the signature with `jwk` and returns the union of the protected and
+    /// unprotected headers.
+    pub fn verify(&self, jwk: &JsonWebKey) -> Result<Header, Error> {
+        self.signature.verify(jwk, &self.payload)
+    }
+
+    /// Returns the decoded payload. Note that it is only authentic once verified.
+    pub fn payload(&self) -> Result<Vec<u8>, Error> {
+        decode(&self.payload)
+    }
+}
+
+impl GeneralJws {
+    /// Returns a JWS of `payload` which has yet to be signed.
+    pub fn new(payload: &[u8]) -> Self {
+        Self {
+            payload: encode(payload),
+            signatures: Vec::new(),
+        }
+    }
+
+    /// Adds a signature made with `jwk`, as by `FlattenedJws::sign`.
+    pub fn add_signature(
+        &mut self,
+        jwk: &JsonWebKey,
+        protected: &Header,
+        unprotected: Option<serde_json::Map<String, serde_json::Value>>,
+    ) -> Result<(), Error> {
+        let signature = JwsSignature::sign(jwk, protected, unprotected, &self.payload)?;
+        self.signatures.push(signature);
+        Ok(())
+    }
+
+    /// Verifies the signatures with `jwk` and returns the headers of the first valid one.
+    /// Returns the error of the last signature if none is valid, and `Error::InvalidSignature`
+    /// if there are none.
+    pub fn verify(&self, jwk: &JsonWebKey) -> Result<Header, Error> {
+        let mut result = Err(Error::InvalidSignature);
+        for signature in &self.signatures {
+            result = signature.verify(jwk, &self.payload);
+            if result.is_ok() {
+                break;
+            }
+        }
+        result
+    }
+
+    /// Returns the decoded payload. Note that it is only authentic once verified.
+    pub fn payload(&self) -> Result<Vec<u8>, Error> {
+        decode(&self.payload)
+    }
+}
+
+impl From<FlattenedJws> for GeneralJws {
+    fn from(jws: FlattenedJws) -> Self {
+        Self {
+            payload: jws.payload,
+            signatures: vec![jws.signature],
+        }
+    }
+}
+
+fn sign(jwk: &JsonWebKey, alg: &Algorithm, signing_input: &[u8]) -> Result<Vec<u8>, Error> {
+    check_algorithm(jwk, alg)?;
+    jwk.key.sign(alg, signing_input)
+}
+
+fn verify(
+    jwk: &JsonWebKey,
+    alg: &Algorithm,
+    signing_input: &[u8],
+    signature: &[u8],
+) -> Result<(), Error> {
+    check_algorithm(jwk, alg)?;
+    jwk.key.verify(alg, signing_input, signature)
+}
+
+fn check_algorithm(jwk: &JsonWebKey, alg: &Algorithm) -> Result<(), Error> {
+    match &jwk.algorithm {
+        Some(key_alg) if key_alg != alg => Err(Error::MismatchedAlgorithm),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the union of the `protected` (base64url-encoded) and `unprotected` headers, which
+/// must be disjoint.
+fn decode_header(
+    protected: Option<&str>,
+    unprotected: Option<&serde_json::Map<String, serde_json::Value>>,
+) -> Result<Header, Error> {
+    let mut header = match protected {
+        Some(protected) => serde_json::from_slice(&decode(protected)?)?,
+        None => serde_json::Map::new(),
+    };
+    for (name, value) in unprotected.into_iter().flatten() {
+        if header.insert(name.clone(), value.clone()).is_some() {
+            return Err(Error::InvalidJws);
+        }
+    }
+    let header: Header = serde_json::from_value(header.into())?;
+    if header.crit.is_some() {
+        return Err(Error::InvalidJws);
+    }
+    Ok(header)
+}
+
+fn encode(bytes: &[u8]) -> String {
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
+fn decode(b64: &str) -> Result<Vec<u8>, Error> {
+    Ok(base64::decode_config(b64, base64::URL_SAFE_NO_PAD)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::Key;
+
+    // RFC 7515 appendix A.1
+    static HS256_JWK: &str = r#"{
+        "kty": "oct",
+        "k": "AyM1SysPpbyDfgZld3umj1qzKObwVMkoqQ-EstJQLr_T-1qS0gZH75aKtMN3Yj0iPS4hcgUuTwjAzZr1Z9CAow"
+    }"#;
+    static HS256_JWS: &str = "eyJ0eXAiOiJKV1QiLA0KICJhbGciOiJIUzI1NiJ9.\
+        eyJpc3MiOiJqb2UiLA0KICJleHAiOjEzMDA4MTkzODAsDQogImh0dHA6Ly9leGFtcGxlLmNvbS9pc19yb290Ijp0cnVlfQ.\
+        dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+
+    #[test]
+    fn verify_rfc7515() {
+        let jwk: JsonWebKey = HS256_JWK.parse().unwrap();
+        let (header, payload) = verify_compact(&jwk, HS256_JWS).unwrap();
+        assert_eq!(header, Header::new(Algorithm::HS256).with_typ("JWT"));
+        assert!(payload.starts_with(b"{\"iss\":\"joe\""));
+
+        let tampered = HS256_JWS.replace(".eyJpc3MiOiJqb2U", ".eyJpc3MiOiJqb2X");
+        assert!(matches!(
+            verify_compact(&jwk, &tampered),
+            Err(Error::InvalidSignature)
+        ));
+        assert!(matches!(
+            verify_compact(&jwk, &HS256_JWS[..HS256_JWS.rfind('.').unwrap()]),
+            Err(Error::InvalidJws)
+        ));
+    }
+
+    #[test]
+    fn sign_compact_round_trip() {
+        let mut jwk = JsonWebKey::new(Key::Symmetric {
+            key: vec![42; 32].into(),
+        });
+        assert!(matches!(
+            Header::for_key(&jwk),
+            Err(Error::MissingAlgorithm)
+        ));
+        jwk.set_algorithm(Algorithm::HS256).unwrap();
+        jwk.key_id = Some("a key".into());
+
+        let header = Header::for_key(&jwk).unwrap().with_typ("example");
+        let token = sign_compact(&jwk, &header, b"payload").unwrap();
+        assert!(token.starts_with(&encode(br#"{"alg":"HS256","kid":"a key","typ":"example"}"#)));
+        assert_eq!(
+            verify_compact(&jwk, &token).unwrap(),
+            (header, b"payload".to_vec())
+        );
+
+        let hs384 = Header::new(Algorithm::HS384);
+        assert!(matches!(
+            sign_compact(&jwk, &hs384, b"payload"),
+            Err(Error::MismatchedAlgorithm)
+        ));
+
+        let mut crit = Header::new(Algorithm::HS256);
+        crit.crit = Some(vec!["exp".into()]);
+        crit.other.insert("exp".into(), 0.into());
+        let token = sign_compact(&jwk, &crit, b"payload").unwrap();
+        assert!(matches!(
+            verify_compact(&jwk, &token),
+            Err(Error::InvalidJws)
+        ));
+    }
+
+    // RFC 7515 appendices A.3 and A.7
+    static ES256_JWK: &str = r#"{
+        "kty": "EC",
+        "crv": "P-256",
+        "x": "f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU",
+        "y": "x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0"
+    }"#;
+    static P256_JWK: &str = r#"{
+        "kty": "EC",
+        "crv": "P-256",
+        "d": "ZoKQ9j4dhIBlMRVrv-QG8P_T9sutv3_95eio9MtpgKg",
+        "x": "QOMHmv96tVlJv-uNqprnDSKIj5AiLTXKRomXYnav0N0",
+        "y": "TjYZoHnctatEE6NCrKmXQdJJPnNzZEX8nBmZde3AY4k"
+    }"#;
+    static ES256_FLATTENED_JWS: &str = r#"{
+        "payload": "eyJpc3MiOiJqb2UiLA0KICJleHAiOjEzMDA4MTkzODAsDQogImh0dHA6Ly9leGFtcGxlLmNvbS9pc19yb290Ijp0cnVlfQ",
+        "protected": "eyJhbGciOiJFUzI1NiJ9",
+        "header": {"kid": "e9bc097a-ce51-4036-9562-d2ade882db0d"},
+        "signature": "DtEhU3ljbEg8L38VWAfUAqOyKAM6-Xx-F4GawxaepmXFCgfTjDxw5djxLa8ISlSApmWQxfKTUJqPP3-Kg6NU1Q"
+    }"#;
+
+    #[test]
+    fn verify_flattened_rfc7515() {
+        let jwk: JsonWebKey = ES256_JWK.parse().unwrap();
+        let jws: FlattenedJws = serde_json::from_str(ES256_FLATTENED_JWS).unwrap();
+        let header = jws.verify(&jwk).unwrap();
+        assert_eq!(
+            header,
+            Header::new(Algorithm::ES256).with_kid("e9bc097a-ce51-4036-9562-d2ade882db0d")
+        );
+        assert!(jws.payload().unwrap().starts_with(b"{\"iss\":\"joe\""));
+
+        let mut duplicated = jws;
+        let header = duplicated.signature.header.as_mut().unwrap();
+        header.insert("alg".into(), "ES256".into());
+        assert!(matches!(duplicated.verify(&jwk), Err(Error::InvalidJws)));
+    }
+
+    #[test]
+    fn sign_json_round_trip() {
+        let mut hs256: JsonWebKey = HS256_JWK.parse().unwrap();
+        hs256.set_algorithm(Algorithm::HS256).unwrap();
+        let es256: JsonWebKey = P256_JWK.parse().unwrap();
+        let mut unprotected = serde_json::Map::new();
+        unprotected.insert("kid".into(), "mac".into());
+
+        let flattened = FlattenedJws::sign(
+            &hs256,
+            &Header::for_key(&hs256).unwrap(),
+            Some(unprotected.clone()),
+            b"payload",
+        )
+        .unwrap();
+        let json = serde_json::to_value(&flattened).unwrap();
+        assert_eq!(json["header"]["kid"], "mac");
+        assert!(json.get("signatures").is_none());
+        assert_eq!(
+            flattened.verify(&hs256).unwrap(),
+            Header::new(Algorithm::HS256).with_kid("mac")
+        );
+        assert_eq!(flattened.payload().unwrap(), b"payload");
+
+        let mut general = GeneralJws::from(flattened);
+        general
+            .add_signature(&es256, &Header::new(Algorithm::ES256), None)
+            .unwrap();
+        assert_eq!(general.signatures.len(), 2);
+        let general: GeneralJws =
+            serde_json::from_str(&serde_json::to_string(&general).unwrap()).unwrap();
+        assert_eq!(general.verify(&hs256).unwrap().alg, Algorithm::HS256);
+        assert_eq!(general.verify(&es256).unwrap().alg, Algorithm::ES256);
+        let other: JsonWebKey = HS256_JWK.replace("AyM1", "AyM2").parse().unwrap();
+        assert!(general.verify(&other).is_err());
+        assert!(matches!(
+            GeneralJws::new(b"payload").verify(&hs256),
+            Err(Error::InvalidSignature)
+        ));
+
+        unprotected.insert("alg".into(), "HS256".into());
+        assert!(matches!(
+            GeneralJws::new(b"payload").add_signature(
+                &hs256,
+                &Header::new(Algorithm::HS256),
+                Some(unprotected)
+            ),
+            Err(Error::InvalidJws)
+        ));
+    }
+}
@@ -0,0 +1,230 @@
+//! Issuing and verifying JWTs with `JsonWebKey`s, the `alg` and `kid` of tokens being
+//! taken from, or matched against, those of the keys.
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    jws::{self, Header},
+    Error, JsonWebKey, JsonWebKeySet,
+};
+
+/// Checks applied to the registered claims of a JWT by `JsonWebKeySet::verify_jwt`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Validation {
+    /// Whether to require an `exp` claim which has not passed.
+    pub validate_exp: bool,
+    /// Whether to check that the `nbf` claim, if any, has passed.
+    pub validate_nbf: bool,
+    /// The number of seconds of clock skew tolerated when checking `exp` and `nbf`.
+    pub leeway: u64,
+    /// The required `iss` claim, if any.
+    pub issuer: Option<String>,
+    /// The audience which must be listed in the `aud` claim, if any.
+    pub audience: Option<String>,
+}
+
+impl Default for Validation {
+    fn default() -> Self {
+        Self {
+            validate_exp: true,
+            validate_nbf: true,
+            leeway: 60,
+            issuer: None,
+            audience: None,
+        }
+    }
+}
+
+impl Validation {
+    fn validate(&self, claims: &serde_json::Value) -> Result<(), Error> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|now| now.as_secs())
+            .unwrap_or_default();
+        let time = |name| match claims.get(name) {
+            Some(time) => time
+                .as_u64()
+                .ok_or(Error::InvalidClaims("invalid time"))
+                .map(Some),
+            None => Ok(None),
+        };
+        if self.validate_exp {
+            match time("exp")? {
+                Some(exp) if exp.saturating_add(self.leeway) < now => {
+                    return Err(Error::InvalidClaims("expired"));
+                }
+                Some(_) => {}
+                None => return Err(Error::InvalidClaims("missing exp")),
+            }
+        }
+        if self.validate_nbf {
+            if let Some(nbf) = time("nbf")? {
+                if nbf > now.saturating_add(self.leeway) {
+                    return Err(Error::InvalidClaims("not yet valid"));
+                }
+            }
+        }
+        if let Some(issuer) = &self.issuer {
+            if claims.get("iss").and_then(|iss| iss.as_str()) != Some(issuer) {
+                return Err(Error::InvalidClaims("wrong issuer"));
+            }
+        }
+        if let Some(audience) = &self.audience {
+            let listed = match claims.get("aud") {
+                Some(serde_json::Value::String(aud)) => aud == audience,
+                Some(serde_json::Value::Array(auds)) => {
+                    auds.iter().any(|aud| aud.as_str() == Some(audience))
+                }
+                _ => false,
+            };
+            if !listed {
+                return Err(Error::InvalidClaims("wrong audience"));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl JsonWebKey {
+    /// Issues a JWT of `claims`, signed using the `alg` of this key and having its `kid`.
+    /// Returns `Error::MissingAlgorithm` if this key has no `alg`.
+    pub fn sign_jwt(&self, claims: &impl Serialize) -> Result<String, Error> {
+        let header = Header::for_key(self)?.with_typ("JWT");
+        jws::sign_compact(self, &header, &serde_json::to_vec(claims)?)
+    }
+}
+
+impl JsonWebKeySet {
+    /// Verifies the JWT `token` using the key of this set selected by the `kid` and `alg` of
+    /// its header, as by `candidate_keys`, then checks its claims according to `validation`.
+    /// Returns `Error::InvalidSignature` if no key of this set verifies the token, and
+    /// `Error::InvalidClaims` if the claims don't pass `validation`.
+    pub fn verify_jwt<C: DeserializeOwned>(
+        &self,
+        token: &str,
+        validation: &Validation,
+    ) -> Result<C, Error> {
+        let header = jws::decode_compact_header(token)?;
+        let payload = self
+            .candidate_keys(header.kid.as_deref(), &header.alg)
+            .into_iter()
+            .find_map(|jwk| jws::verify_compact(jwk, token).ok())
+            .ok_or(Error::InvalidSignature)?
+            .1;
+        let claims: serde_json::Value = serde_json::from_slice(&payload)?;
+        validation.validate(&claims)?;
+        Ok(serde_json::from_value(claims)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde::Deserialize;
+
+    use crate::{Algorithm, Key};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Claims {
+        sub: String,
+        exp: u64,
+    }
+
+    fn now() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    fn hs256_jwk(kid: &str, k: u8) -> JsonWebKey {
+        let mut jwk = JsonWebKey::new(Key::Symmetric {
+            key: vec![k; 32].into(),
+        });
+        jwk.set_algorithm(Algorithm::HS256).unwrap();
+        jwk.key_id = Some(kid.into());
+        jwk
+    }
+
+    #[test]
+    fn sign_verify_jwt() {
+        let old = hs256_jwk("old", 1);
+        let new = hs256_jwk("new", 2);
+        let set: JsonWebKeySet = vec![old.clone(), new.clone()].into_iter().collect();
+
+        let claims = Claims {
+            sub: "jsonwebkey".into(),
+            exp: now() + 600,
+        };
+        let token = new.sign_jwt(&claims).unwrap();
+        let header = jws::decode_compact_header(&token).unwrap();
+        assert_eq!(header.kid.as_deref(), Some("new"));
+        assert_eq!(header.typ.as_deref(), Some("JWT"));
+        let verified: Claims = set.verify_jwt(&token, &Validation::default()).unwrap();
+        assert_eq!(verified, claims);
+
+        let unknown = hs256_jwk("new", 3).sign_jwt(&claims).unwrap();
+        assert!(matches!(
+            set.verify_jwt::<Claims>(&unknown, &Validation::default()),
+            Err(Error::InvalidSignature)
+        ));
+
+        let expired = Claims {
+            exp: now() - 120,
+            ..claims
+        };
+        let token = old.sign_jwt(&expired).unwrap();
+        assert!(matches!(
+            set.verify_jwt::<Claims>(&token, &Validation::default()),
+            Err(Error::InvalidClaims("expired"))
+        ));
+        let lenient = Validation {
+            validate_exp: false,
+            ..Default::default()
+        };
+        assert!(set.verify_jwt::<Claims>(&token, &lenient).is_ok());
+    }
+
+    #[test]
+    fn validate_claims() {
+        let validation = Validation {
+            validate_exp: false,
+            issuer: Some("https://issuer.example".into()),
+            audience: Some("api".into()),
+            ..Default::default()
+        };
+        let claims = serde_json::json!({
+            "iss": "https://issuer.example",
+            "aud": ["web", "api"],
+            "nbf": now(),
+        });
+        validation.validate(&claims).unwrap();
+
+        let invalid = [
+            (serde_json::json!({"aud": "api"}), "wrong issuer"),
+            (
+                serde_json::json!({"iss": "https://issuer.example", "aud": "web"}),
+                "wrong audience",
+            ),
+            (
+                serde_json::json!({
+                    "iss": "https://issuer.example",
+                    "aud": "api",
+                    "nbf": now() + 600,
+                }),
+                "not yet valid",
+            ),
+        ];
+        for (claims, reason) in &invalid {
+            assert!(matches!(
+                validation.validate(claims),
+                Err(Error::InvalidClaims(r)) if r == *reason
+            ));
+        }
+        assert!(matches!(
+            Validation::default().validate(&serde_json::json!({})),
+            Err(Error::InvalidClaims("missing exp"))
+        ));
+    }
+}
@@ -0,0 +1,39 @@
+use crate::{utils::aes, Algorithm, ConversionError, Error, JsonWebKey, Key};
+
+impl Key {
+    /// Wraps (encrypts) the content encryption key `cek` with this key using the JWE key
+    /// management algorithm `alg`, one of A128KW, A192KW, and A256KW
+    /// ([RFC 7518 §4.4](https://tools.ietf.org/html/rfc7518#section-4.4)).
+    pub fn wrap_key(&self, alg: &Algorithm, cek: &[u8]) -> Result<Vec<u8>, Error> {
+        let kek = key_encryption_key(self, alg)?;
+        aes::key_wrap(kek, cek).ok_or_else(|| {
+            ConversionError::InvalidLength(format!(
+                "a wrapped key must have a multiple of 8 bytes and at least 16, but had {}",
+                cek.len()
+            ))
+            .into()
+        })
+    }
+
+    /// Unwraps (decrypts) a content encryption key wrapped by `wrap_key`, returning
+    /// `Error::DecryptionFailed` if it was not wrapped with this key or has been altered.
+    /// Zeroizing the returned key is the caller's responsibility.
+    pub fn unwrap_key(&self, alg: &Algorithm, wrapped: &[u8]) -> Result<Vec<u8>, Error> {
+        let kek = key_encryption_key(self, alg)?;
+        let cek = aes::key_unwrap(kek, wrapped).ok_or(Error::DecryptionFailed)?;
+        Ok(cek.to_vec())
+    }
+}
+
+fn key_encryption_key<'a>(key: &'a Key, alg: &Algorithm) -> Result<&'a [u8], Error> {
+    JsonWebKey::validate_algorithm(alg, key)?;
+    match (alg, key) {
+        (Algorithm::A128KW | Algorithm::A192KW | Algorithm::A256KW, Key::Symmetric { key }) => {
+            if ![16, 24, 32].contains(&key.len()) {
+                return Err(Error::MismatchedAlgorithm);
+            }
+            Ok(key)
+        }
+        _ => Err(ConversionError::UnsupportedKey.into()),
+    }
+}
@@ -0,0 +1,623 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Algorithm, Error, JsonWebKey, KeyUse};
+
+/// A JWK Set, as per [RFC 7517 §5](https://tools.ietf.org/html/rfc7517#section-5).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonWebKeySet {
+    /// The keys in this set. A missing or `null` `keys` member is read as an empty set.
+    #[serde(default, deserialize_with = "deserialize_keys")]
+    pub keys: Vec<JsonWebKey>,
+}
+
+fn deserialize_keys<'de, D: serde::Deserializer<'de>>(d: D) -> Result<Vec<JsonWebKey>, D::Error> {
+    Ok(Option::deserialize(d)?.unwrap_or_default())
+}
+
+impl JsonWebKeySet {
+    /// Parses a JSON document containing any mix of JWKs and JWK Sets, possibly nested in arrays,
+    /// into a single flat set. Keys having the same thumbprint as a previous key are dropped.
+    pub fn from_mixed_json(bytes: &[u8]) -> Result<Self, Error> {
+        fn flatten(value: serde_json::Value, keys: &mut Vec<JsonWebKey>) -> Result<(), Error> {
+            match value {
+                serde_json::Value::Array(values) => {
+                    for value in values {
+                        flatten(value, keys)?;
+                    }
+                }
+                serde_json::Value::Object(mut obj) if obj.contains_key("keys") => {
+                    match obj.remove("keys").unwrap() {
+                        serde_json::Value::Null => {}
+                        set_keys => flatten(set_keys, keys)?,
+                    }
+                }
+                value => {
+                    let jwk: JsonWebKey = serde_json::from_value(value)?;
+                    if let Some(alg) = &jwk.algorithm {
+                        JsonWebKey::validate_algorithm(alg, &jwk.key)?;
+                    }
+                    keys.push(jwk);
+                }
+            }
+            Ok(())
+        }
+
+        let mut keys = Vec::new();
+        flatten(serde_json::from_slice(bytes)?, &mut keys)?;
+
+        let mut thumbprints = std::collections::HashSet::new();
+        keys.retain(|jwk| thumbprints.insert(jwk.key.thumbprint()));
+        Ok(Self { keys })
+    }
+
+    /// Parses a key or keys in any of the supported formats: a JWK, a JWK Set, a JSON array
+    /// of those (see `from_mixed_json`) or, with the `pkcs-convert` feature, PEM-armored keys
+    /// in any of the formats read by `Key::from_pem` and certificates, whose public keys are
+    /// read as by `JsonWebKey::from_certificate_der`. Other PEM blocks, including encrypted
+    /// keys, are skipped.
+    pub fn parse_any(input: &[u8]) -> Result<Self, Error> {
+        #[cfg(feature = "pkcs-convert")]
+        {
+            let pem = std::str::from_utf8(input).ok().map(str::trim_start);
+            if let Some(pem) = pem.filter(|pem| pem.starts_with("-----BEGIN ")) {
+                let keys: Vec<_> = crate::utils::pem::decode_blocks(pem)?
+                    .iter()
+                    .filter_map(|(label, der)| match label.as_str() {
+                        "CERTIFICATE" => Some(JsonWebKey::from_certificate_der(der)),
+                        _ => crate::Key::from_pem_block(label, der)
+                            .map(|key| key.map(JsonWebKey::new)),
+                    })
+                    .collect::<Result<_, _>>()?;
+                if keys.is_empty() {
+                    return Err(crate::ConversionError::InvalidPem.into());
+                }
+                return Ok(Self { keys });
+            }
+        }
+        Self::from_mixed_json(input)
+    }
+
+    /// Parses a JSON object mapping key ids to PEM-encoded X.509 certificates, the format of
+    /// Google's legacy certificate endpoints (e.g. for Firebase ID tokens), into a set of their
+    /// public keys having those `kid`s along with their `x5c` and `x5t#S256` members.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn from_certificate_map(json: &str) -> Result<Self, Error> {
+        let certificates: std::collections::BTreeMap<String, String> = serde_json::from_str(json)?;
+        let keys = certificates
+            .into_iter()
+            .map(|(kid, pem)| {
+                let mut jwk = JsonWebKey::from_certificate_pem(&pem)?;
+                jwk.key_id = Some(kid);
+                Ok(jwk)
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(Self { keys })
+    }
+
+    /// Converts each key and certificate block of a PEM file into a JWK whose `kid` is its
+    /// RFC 7638 thumbprint. Keys may be in any of the formats read by `Key::from_pem`, and
+    /// `CERTIFICATE` blocks yield their public keys along with their `x5c` and `x5t#S256`
+    /// members. `EC PARAMETERS` blocks are skipped, but any other block (e.g., an encrypted key)
+    /// is an error, so that no key is silently left out.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn from_pem_bundle(pem: &str) -> Result<Self, Error> {
+        use crate::{utils::pem, ConversionError, Key};
+
+        let blocks = pem::decode_blocks(pem)?;
+        if blocks.is_empty() {
+            return Err(ConversionError::InvalidPem.into());
+        }
+        let keys = blocks
+            .into_iter()
+            .filter(|(label, _der)| label != "EC PARAMETERS")
+            .map(|(label, der)| {
+                let jwk = match label.as_str() {
+                    "CERTIFICATE" => JsonWebKey::from_certificate_der(&der)?,
+                    _ => JsonWebKey::new(
+                        Key::from_pem_block(&label, &der)
+                            .unwrap_or(Err(ConversionError::UnsupportedKey))?,
+                    ),
+                };
+                Ok(jwk.with_kid_from_thumbprint())
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(Self { keys })
+    }
+
+    /// Returns the keys that may be used to verify a token having the provided `kid` and `alg`
+    /// header parameters, ordered from most to least preferred:
+    ///
+    /// 1. keys having both the requested `kid` and `alg`,
+    /// 2. keys having the requested `kid` and a key type usable with `alg`,
+    /// 3. keys having no `kid` (or any `kid`, if none was requested) which are usable with `alg`.
+    ///
+    /// A key is never returned more than once, and keys having an `alg` other than the requested
+    /// one are only considered when their `kid` matches.
+    pub fn candidate_keys(&self, kid: Option<&str>, alg: &Algorithm) -> Vec<&JsonWebKey> {
+        let kid_matches = |jwk: &JsonWebKey| kid.is_some() && jwk.key_id.as_deref() == kid;
+        let key_matches = |jwk: &JsonWebKey| JsonWebKey::validate_algorithm(alg, &jwk.key).is_ok();
+
+        let exact = self
+            .keys
+            .iter()
+            .filter(|jwk| kid_matches(jwk) && jwk.algorithm.as_ref() == Some(alg));
+        let by_kid = self.keys.iter().filter(|jwk| {
+            kid_matches(jwk) && jwk.algorithm.as_ref() != Some(alg) && key_matches(jwk)
+        });
+        let by_alg = self.keys.iter().filter(|jwk| {
+            (kid.is_none() || jwk.key_id.is_none())
+                && jwk.algorithm.as_ref().map(|a| a == alg).unwrap_or(true)
+                && key_matches(jwk)
+        });
+
+        exact.chain(by_kid).chain(by_alg).collect()
+    }
+
+    /// Returns the first key having the provided `kid`.
+    pub fn find_by_kid(&self, kid: &str) -> Option<&JsonWebKey> {
+        self.keys
+            .iter()
+            .find(|jwk| jwk.key_id.as_deref() == Some(kid))
+    }
+
+    /// Returns the keys which may be used with `alg`: those whose key type is suitable for it
+    /// and which have either no `alg` or exactly this one.
+    pub fn find_for_algorithm(&self, alg: &Algorithm) -> Vec<&JsonWebKey> {
+        self.keys
+            .iter()
+            .filter(|jwk| {
+                jwk.algorithm.as_ref().map(|a| a == alg).unwrap_or(true)
+                    && JsonWebKey::validate_algorithm(alg, &jwk.key).is_ok()
+            })
+            .collect()
+    }
+
+    /// Returns the keys which may be used for `key_use`, i.e. those having this `use` or none.
+    pub fn find_by_use(&self, key_use: KeyUse) -> Vec<&JsonWebKey> {
+        self.keys
+            .iter()
+            .filter(|jwk| jwk.key_use.map(|u| u == key_use).unwrap_or(true))
+            .collect()
+    }
+
+    /// Adds the keys of `other` to this set, keeping a single key per RFC 7638 thumbprint.
+    /// Of keys sharing a thumbprint, the one carrying the most metadata (`kid`, `alg`, `use`
+    /// and `key_ops`) is kept, in the position of the first; ties favor the earlier key.
+    pub fn merge(&mut self, other: JsonWebKeySet) {
+        fn metadata(jwk: &JsonWebKey) -> usize {
+            jwk.key_id.is_some() as usize
+                + jwk.algorithm.is_some() as usize
+                + jwk.key_use.is_some() as usize
+                + !jwk.key_ops.is_empty() as usize
+        }
+
+        let mut positions = std::collections::HashMap::new();
+        let mut keys: Vec<JsonWebKey> = Vec::with_capacity(self.keys.len() + other.keys.len());
+        for jwk in self.keys.drain(..).chain(other.keys) {
+            match positions.entry(jwk.key.thumbprint()) {
+                std::collections::hash_map::Entry::Occupied(pos) => {
+                    let kept = &mut keys[*pos.get()];
+                    if metadata(&jwk) > metadata(kept) {
+                        *kept = jwk;
+                    }
+                }
+                std::collections::hash_map::Entry::Vacant(pos) => {
+                    pos.insert(keys.len());
+                    keys.push(jwk);
+                }
+            }
+        }
+        self.keys = keys;
+    }
+
+    /// Parses a JWK Set published as a signed JWT whose claims contain the `keys` member,
+    /// such as the signed JWKS documents of OpenID Federation. The signature is verified with
+    /// `trust_anchor`, using its `alg` if set or otherwise the `alg` of the token header.
+    #[cfg(feature = "jwt-convert")]
+    pub fn from_signed_jwt(token: &str, trust_anchor: &JsonWebKey) -> Result<Self, Error> {
+        use jsonwebtoken as jwt;
+        use std::convert::TryFrom;
+
+        let header_alg = jwt::decode_header(token)?.alg;
+        let alg: Algorithm = serde_json::from_value(serde_json::to_value(header_alg)?)?;
+        if trust_anchor
+            .algorithm
+            .as_ref()
+            .map(|a| a != &alg)
+            .unwrap_or(false)
+        {
+            return Err(Error::MismatchedAlgorithm);
+        }
+        JsonWebKey::validate_algorithm(&alg, &trust_anchor.key)?;
+
+        #[derive(Deserialize)]
+        struct Claims {
+            #[serde(flatten)]
+            set: JsonWebKeySet,
+            exp: Option<u64>,
+        }
+
+        // `jsonwebtoken` rejects tokens lacking `exp` when validating it, but it is optional here.
+        let decoding_key = trust_anchor.key.try_to_decoding_key()?;
+        let mut validation = jwt::Validation::new(jwt::Algorithm::try_from(alg)?);
+        validation.validate_exp = false;
+        let claims: Claims = jwt::decode(token, &decoding_key, &validation)?.claims;
+        if let Some(exp) = claims.exp {
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|now| now.as_secs())
+                .unwrap_or_default();
+            if exp < now {
+                let expired = jwt::errors::ErrorKind::ExpiredSignature;
+                return Err(jwt::errors::Error::from(expired).into());
+            }
+        }
+        let set = claims.set;
+        for jwk in set.iter() {
+            if let Some(alg) = &jwk.algorithm {
+                JsonWebKey::validate_algorithm(alg, &jwk.key)?;
+            }
+        }
+        Ok(set)
+    }
+
+    /// Returns an iterator over the keys in this set.
+    pub fn iter(&self) -> std::slice::Iter<'_, JsonWebKey> {
+        self.keys.iter()
+    }
+}
+
+/// A source of keys for verifying tokens, selected by their `kid` and `alg` header parameters.
+pub trait KeyResolver {
+    /// Returns the preferred key for a token having the provided `kid` and `alg`, if any.
+    fn resolve(&self, kid: Option<&str>, alg: &Algorithm) -> Option<&JsonWebKey>;
+}
+
+impl KeyResolver for JsonWebKeySet {
+    /// Returns the first of the `candidate_keys`.
+    fn resolve(&self, kid: Option<&str>, alg: &Algorithm) -> Option<&JsonWebKey> {
+        self.candidate_keys(kid, alg).into_iter().next()
+    }
+}
+
+impl KeyResolver for JsonWebKey {
+    /// Returns this key if it is usable with `alg` and its `kid`, if any, matches.
+    fn resolve(&self, kid: Option<&str>, alg: &Algorithm) -> Option<&JsonWebKey> {
+        let kid_matches = match (kid, &self.key_id) {
+            (Some(kid), Some(key_id)) => kid == key_id,
+            _ => true,
+        };
+        let alg_matches = self.algorithm.as_ref().map(|a| a == alg).unwrap_or(true)
+            && JsonWebKey::validate_algorithm(alg, &self.key).is_ok();
+        Some(self).filter(|_| kid_matches && alg_matches)
+    }
+}
+
+impl<T: KeyResolver + ?Sized> KeyResolver for std::sync::Arc<T> {
+    fn resolve(&self, kid: Option<&str>, alg: &Algorithm) -> Option<&JsonWebKey> {
+        (**self).resolve(kid, alg)
+    }
+}
+
+impl std::str::FromStr for JsonWebKeySet {
+    type Err = Error;
+    fn from_str(json: &str) -> Result<Self, Self::Err> {
+        let set: Self = serde_json::from_str(json)?;
+        for jwk in set.iter() {
+            if let Some(alg) = &jwk.algorithm {
+                JsonWebKey::validate_algorithm(alg, &jwk.key)?;
+            }
+        }
+        Ok(set)
+    }
+}
+
+impl std::fmt::Display for JsonWebKeySet {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if f.alternate() {
+            write!(f, "{}", serde_json::to_string_pretty(self).unwrap())
+        } else {
+            write!(f, "{}", serde_json::to_string(self).unwrap())
+        }
+    }
+}
+
+impl IntoIterator for JsonWebKeySet {
+    type Item = JsonWebKey;
+    type IntoIter = std::vec::IntoIter<JsonWebKey>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.keys.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a JsonWebKeySet {
+    type Item = &'a JsonWebKey;
+    type IntoIter = std::slice::Iter<'a, JsonWebKey>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.keys.iter()
+    }
+}
+
+impl std::iter::FromIterator<JsonWebKey> for JsonWebKeySet {
+    fn from_iter<I: IntoIterator<Item = JsonWebKey>>(iter: I) -> Self {
+        Self {
+            keys: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{Key, KeyOps};
+
+    fn oct(kid: Option<&str>, alg: Option<Algorithm>) -> JsonWebKey {
+        JsonWebKey {
+            key: Box::new(Key::Symmetric {
+                key: vec![42; 32].into(),
+            }),
+            key_use: None,
+            critical: None,
+            certificate_url: None,
+            certificate_chain: None,
+            certificate_sha1_thumbprint: None,
+            certificate_sha256_thumbprint: None,
+            key_ops: KeyOps::empty(),
+            key_id: kid.map(Into::into),
+            algorithm: alg,
+        }
+    }
+
+    #[test]
+    fn candidate_keys_order() {
+        let set = JsonWebKeySet {
+            keys: vec![
+                oct(None, None),
+                oct(Some("a"), None),
+                oct(Some("b"), Some(Algorithm::HS256)),
+                oct(Some("a"), Some(Algorithm::HS256)),
+                oct(None, Some(Algorithm::HS256)),
+            ],
+        };
+        let candidates = set.candidate_keys(Some("a"), &Algorithm::HS256);
+        assert_eq!(
+            candidates,
+            vec![&set.keys[3], &set.keys[1], &set.keys[0], &set.keys[4]]
+        );
+    }
+
+    #[test]
+    fn from_mixed_json() {
+        let json = r#"[
+            { "kty": "oct", "k": "AQID", "kid": "a" },
+            { "keys": [
+                { "kty": "oct", "k": "BAUG" },
+                { "keys": [{ "kty": "oct", "k": "AQID", "kid": "dup" }] }
+            ] },
+            [{ "kty": "oct", "k": "BwgJ" }]
+        ]"#;
+        let set = JsonWebKeySet::from_mixed_json(json.as_bytes()).unwrap();
+        let kids: Vec<_> = set.keys.iter().map(|k| k.key_id.as_deref()).collect();
+        assert_eq!(kids, vec![Some("a"), None, None]);
+        assert_eq!(set.keys.len(), 3);
+        assert!(JsonWebKeySet::from_mixed_json(br#"[{ "kty": "oct" }]"#).is_err());
+    }
+
+    #[test]
+    fn parse_any_json() {
+        let jwk = br#"{ "kty": "oct", "k": "AQID" }"#;
+        assert_eq!(JsonWebKeySet::parse_any(jwk).unwrap().keys.len(), 1);
+        let jwks =
+            br#" { "keys": [{ "kty": "oct", "k": "AQID" }, { "kty": "oct", "k": "BAUG" }] }"#;
+        assert_eq!(JsonWebKeySet::parse_any(jwks).unwrap().keys.len(), 2);
+        let mixed =
+            br#"[{ "kty": "oct", "k": "AQID" }, { "keys": [{ "kty": "oct", "k": "BAUG" }] }]"#;
+        assert_eq!(JsonWebKeySet::parse_any(mixed).unwrap().keys.len(), 2);
+        assert!(JsonWebKeySet::parse_any(b"garbage").is_err());
+    }
+
+    #[cfg(feature = "pkcs-convert")]
+    #[test]
+    fn parse_any_pem() {
+        let jwk: JsonWebKey = r#"{
+            "kty": "EC",
+            "crv": "P-256",
+            "d": "ZoKQ9j4dhIBlMRVrv-QG8P_T9sutv3_95eio9MtpgKg",
+            "x": "QOMHmv96tVlJv-uNqprnDSKIj5AiLTXKRomXYnav0N0",
+            "y": "TjYZoHnctatEE6NCrKmXQdJJPnNzZEX8nBmZde3AY4k"
+        }"#
+        .parse()
+        .unwrap();
+        let pem = format!(
+            "\n{}{}",
+            jwk.key.to_pem(),
+            jwk.key.to_public().unwrap().to_pem()
+        );
+        let set = JsonWebKeySet::parse_any(pem.as_bytes()).unwrap();
+        assert_eq!(set.keys.len(), 2);
+        assert_eq!(set.keys[0].key, jwk.key);
+        assert!(!set.keys[1].key.is_private());
+
+        // Type-specific encodings are read, and non-key blocks are skipped.
+        let pem = format!(
+            "-----BEGIN EC PARAMETERS-----\nBggqhkjOPQMBBw==\n-----END EC PARAMETERS-----\n{}",
+            jwk.key.try_to_sec1_pem().unwrap()
+        );
+        let set = JsonWebKeySet::parse_any(pem.as_bytes()).unwrap();
+        assert_eq!(set.keys.len(), 1);
+        assert_eq!(set.keys[0].key, jwk.key);
+
+        assert!(JsonWebKeySet::parse_any(b"-----BEGIN PUBLIC KEY-----\nAAAA").is_err());
+        assert!(JsonWebKeySet::parse_any(
+            b"-----BEGIN EC PARAMETERS-----\nBggqhkjOPQMBBw==\n-----END EC PARAMETERS-----\n"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn deserialize_missing_keys() {
+        let set: JsonWebKeySet = serde_json::from_str("{}").unwrap();
+        assert!(set.keys.is_empty());
+        let set: JsonWebKeySet = serde_json::from_str(r#"{"keys":null}"#).unwrap();
+        assert!(set.keys.is_empty());
+        assert!(serde_json::from_str::<JsonWebKeySet>(r#"{"keys":{}}"#).is_err());
+        let set = JsonWebKeySet::from_mixed_json(br#"[{"keys":null}]"#).unwrap();
+        assert!(set.keys.is_empty());
+    }
+
+    #[test]
+    fn candidate_keys_no_kid() {
+        let set = JsonWebKeySet {
+            keys: vec![
+                oct(Some("a"), Some(Algorithm::HS256)),
+                oct(None, Some(Algorithm::RS256)),
+            ],
+        };
+        assert_eq!(
+            set.candidate_keys(None, &Algorithm::HS256),
+            vec![&set.keys[0]]
+        );
+        assert!(set.candidate_keys(None, &Algorithm::ES256).is_empty());
+    }
+
+    #[test]
+    fn from_str_display_iter() {
+        let json = r#"{"keys":[{"kty":"oct","k":"AQID","kid":"a"},{"kty":"oct","k":"BAUG"}]}"#;
+        let set: JsonWebKeySet = json.parse().unwrap();
+        assert_eq!(set.to_string(), json);
+        assert_eq!(set.to_string().parse::<JsonWebKeySet>().unwrap(), set);
+
+        let kids: Vec<_> = set.iter().map(|jwk| jwk.key_id.as_deref()).collect();
+        assert_eq!(kids, vec![Some("a"), None]);
+        assert_eq!((&set).into_iter().count(), 2);
+        let copy: JsonWebKeySet = set.clone().into_iter().collect();
+        assert_eq!(copy, set);
+
+        let bad_alg = r#"{"keys":[{"kty":"oct","k":"AQID","alg":"ES256"}]}"#;
+        assert!(bad_alg.parse::<JsonWebKeySet>().is_err());
+    }
+
+    #[test]
+    fn find_keys() {
+        let mut sig = oct(Some("sig"), None);
+        sig.key_use = Some(KeyUse::Signing);
+        let mut enc = oct(Some("enc"), Some(Algorithm::A256KW));
+        enc.key_use = Some(KeyUse::Encryption);
+        let set = JsonWebKeySet {
+            keys: vec![sig, enc, oct(None, Some(Algorithm::HS256))],
+        };
+
+        assert_eq!(set.find_by_kid("enc"), Some(&set.keys[1]));
+        assert_eq!(set.find_by_kid("nope"), None);
+
+        assert_eq!(
+            set.find_for_algorithm(&Algorithm::HS256),
+            vec![&set.keys[0], &set.keys[2]]
+        );
+        assert_eq!(
+            set.find_for_algorithm(&Algorithm::A256KW),
+            vec![&set.keys[0], &set.keys[1]]
+        );
+        assert!(set.find_for_algorithm(&Algorithm::ES256).is_empty());
+
+        assert_eq!(
+            set.find_by_use(KeyUse::Signing),
+            vec![&set.keys[0], &set.keys[2]]
+        );
+        assert_eq!(
+            set.find_by_use(KeyUse::Encryption),
+            vec![&set.keys[1], &set.keys[2]]
+        );
+    }
+
+    #[test]
+    fn merge() {
+        let other_key = |kid: Option<&str>| {
+            let mut jwk = oct(kid, None);
+            jwk.key = Box::new(Key::Symmetric {
+                key: vec![7; 32].into(),
+            });
+            jwk
+        };
+        let mut set = JsonWebKeySet {
+            keys: vec![oct(None, None), other_key(Some("b")), oct(None, None)],
+        };
+        set.merge(JsonWebKeySet {
+            keys: vec![oct(Some("a"), Some(Algorithm::HS256)), other_key(None)],
+        });
+        assert_eq!(
+            set.keys,
+            vec![oct(Some("a"), Some(Algorithm::HS256)), other_key(Some("b"))]
+        );
+    }
+
+    #[test]
+    fn key_resolver() {
+        fn resolve_with<'a>(
+            resolver: &'a impl KeyResolver,
+            kid: Option<&str>,
+        ) -> Option<&'a JsonWebKey> {
+            resolver.resolve(kid, &Algorithm::HS256)
+        }
+
+        let set = std::sync::Arc::new(JsonWebKeySet {
+            keys: vec![oct(Some("a"), Some(Algorithm::HS256)), oct(None, None)],
+        });
+        assert_eq!(resolve_with(&set, Some("a")), Some(&set.keys[0]));
+        assert_eq!(resolve_with(&set, Some("b")), Some(&set.keys[1]));
+        assert_eq!(resolve_with(&set, None), Some(&set.keys[0]));
+        assert_eq!(set.resolve(Some("a"), &Algorithm::ES256), None);
+
+        let jwk = oct(Some("a"), None);
+        assert_eq!(resolve_with(&jwk, Some("a")), Some(&jwk));
+        assert_eq!(resolve_with(&jwk, None), Some(&jwk));
+        assert_eq!(resolve_with(&jwk, Some("b")), None);
+        assert_eq!(jwk.resolve(None, &Algorithm::RS256), None);
+    }
+
+    #[cfg(feature = "jwt-convert")]
+    #[test]
+    fn from_signed_jwt() {
+        use jsonwebtoken as jwt;
+
+        let anchor = oct(Some("anchor"), Some(Algorithm::HS256));
+        let claims = serde_json::json!({
+            "iss": "https://federation.example",
+            "keys": [{ "kty": "oct", "k": "AQID", "kid": "a" }],
+        });
+        let sign = |alg, key: &JsonWebKey| {
+            let header = jwt::Header::new(alg);
+            jwt::encode(&header, &claims, &key.key.to_encoding_key()).unwrap()
+        };
+
+        let token = sign(jwt::Algorithm::HS256, &anchor);
+        let set = JsonWebKeySet::from_signed_jwt(&token, &anchor).unwrap();
+        assert_eq!(set.keys.len(), 1);
+        assert_eq!(set.keys[0].key_id.as_deref(), Some("a"));
+
+        let mut other_anchor = anchor.clone();
+        *other_anchor.key = Key::Symmetric {
+            key: vec![7; 32].into(),
+        };
+        assert!(matches!(
+            JsonWebKeySet::from_signed_jwt(&token, &other_anchor),
+            Err(Error::Jwt(_))
+        ));
+
+        let token = sign(jwt::Algorithm::HS384, &anchor);
+        assert!(matches!(
+            JsonWebKeySet::from_signed_jwt(&token, &anchor),
+            Err(Error::MismatchedAlgorithm)
+        ));
+
+        let mut expired = claims.clone();
+        expired["exp"] = 1.into();
+        let header = jwt::Header::new(jwt::Algorithm::HS256);
+        let token = jwt::encode(&header, &expired, &anchor.key.to_encoding_key()).unwrap();
+        assert!(matches!(
+            JsonWebKeySet::from_signed_jwt(&token, &anchor),
+            Err(Error::Jwt(_))
+        ));
+    }
+}
@@ -0,0 +1,388 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use crate::{Algorithm, Error, JsonWebKey, JsonWebKeySet, KeySpec, KeyUse};
+
+/// Tracks the lifecycle of signing keys: the active key, used to sign new tokens, and the
+/// retired keys, which are kept for verifying already issued tokens until their retirement
+/// TTL has passed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyStore {
+    current: Option<JsonWebKey>,
+    retired: Vec<RetiredKey>,
+    retirement_ttl: Duration,
+}
+
+/// A key which has been replaced by `KeyStore::rotate`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetiredKey {
+    pub jwk: JsonWebKey,
+    /// The time, in seconds since the Unix epoch, after which the key is dropped.
+    pub expires_at: u64,
+}
+
+impl KeyStore {
+    /// Creates an empty store whose retired keys remain valid for verification for
+    /// `retirement_ttl`, which should be at least the lifetime of the issued tokens.
+    pub fn new(retirement_ttl: Duration) -> Self {
+        Self {
+            current: None,
+            retired: Vec::new(),
+            retirement_ttl,
+        }
+    }
+
+    /// Generates a new signing key of the kind given by `spec`, which replaces the current key,
+    /// and returns it. The key has `use` `sig`, a thumbprint `kid`, and the `alg` implied by
+    /// `spec`: HS256, HS384, or HS512 (depending on the size) for symmetric keys, ES256,
+    /// ES384, ES256K, or EdDSA otherwise. The previous key is retired and expired keys are
+    /// dropped. Returns `Error::MismatchedAlgorithm` if `spec` isn't a kind of signing key.
+    pub fn rotate(&mut self, spec: KeySpec) -> Result<&JsonWebKey, Error> {
+        let alg = signing_algorithm(spec).ok_or(Error::MismatchedAlgorithm)?;
+        let jwk = JsonWebKey::generate(spec)
+            .with_use(KeyUse::Signing)
+            .with_algorithm(alg)
+            .with_thumbprint_kid()
+            .build()?;
+        let now = now();
+        if let Some(previous) = self.current.replace(jwk) {
+            self.retired.push(RetiredKey {
+                jwk: previous,
+                expires_at: now.saturating_add(self.retirement_ttl.as_secs()),
+            });
+        }
+        self.prune_at(now);
+        Ok(self.current.as_ref().unwrap())
+    }
+
+    /// Returns the key with which new tokens should be signed, if `rotate` has been called.
+    pub fn current(&self) -> Option<&JsonWebKey> {
+        self.current.as_ref()
+    }
+
+    /// Returns the retired keys, oldest first, including those that have expired since the
+    /// last call to `rotate` or `prune`.
+    pub fn retired(&self) -> &[RetiredKey] {
+        &self.retired
+    }
+
+    pub fn retirement_ttl(&self) -> Duration {
+        self.retirement_ttl
+    }
+
+    /// Sets the retirement TTL of keys retired from now on.
+    pub fn set_retirement_ttl(&mut self, retirement_ttl: Duration) {
+        self.retirement_ttl = retirement_ttl;
+    }
+
+    /// Drops the retired keys which have expired.
+    pub fn prune(&mut self) {
+        self.prune_at(now());
+    }
+
+    /// Returns the public parts of the current key and of the unexpired retired keys, newest
+    /// first, e.g. for publishing at a `jwks_uri`. Symmetric keys have no public parts and are
+    /// left out.
+    pub fn verification_set(&self) -> JsonWebKeySet {
+        let now = now();
+        let retired = self
+            .retired
+            .iter()
+            .rev()
+            .filter(|retired| retired.expires_at > now)
+            .map(|retired| &retired.jwk);
+        self.current
+            .iter()
+            .chain(retired)
+            .filter_map(|jwk| {
+                Some(JsonWebKey {
+                    key: Box::new(jwk.key.to_public()?.into_owned()),
+                    ..jwk.clone()
+                })
+            })
+            .collect()
+    }
+
+    fn prune_at(&mut self, now: u64) {
+        self.retired.retain(|retired| retired.expires_at > now);
+    }
+}
+
+/// A `KeyStore` persisted as a JSON file, for services which can't keep their keys in a KMS.
+///
+/// The file is replaced atomically (by writing a new, uniquely named temporary file next to it,
+/// then renaming it), and on Unix it is only readable and writable by its owner. Changes made to the file by
+/// another process are detected, and are not overwritten.
+#[derive(Debug)]
+pub struct FileKeyStore {
+    path: PathBuf,
+    store: KeyStore,
+    /// The SHA-256 digest of the file as last read or written, or `None` if it didn't exist.
+    digest: Option<Vec<u8>>,
+}
+
+/// The contents of the file of a `FileKeyStore`.
+#[derive(Serialize, Deserialize)]
+struct StoreFile {
+    current: Option<JsonWebKey>,
+    #[serde(default)]
+    retired: Vec<RetiredKey>,
+}
+
+impl FileKeyStore {
+    /// Opens the store persisted at `path`, or an empty store if the file doesn't exist yet
+    /// (it is created by the first `rotate`). `retirement_ttl` is as in `KeyStore::new`.
+    pub fn open(path: impl Into<PathBuf>, retirement_ttl: Duration) -> Result<Self, Error> {
+        let mut file_store = Self {
+            path: path.into(),
+            store: KeyStore::new(retirement_ttl),
+            digest: None,
+        };
+        file_store.reload()?;
+        Ok(file_store)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn store(&self) -> &KeyStore {
+        &self.store
+    }
+
+    /// Rotates the signing key as by `KeyStore::rotate`, and saves the store.
+    /// Returns `Error::KeyStoreModified` if the file was changed since it was last read or
+    /// written, in which case nothing is saved; `reload` to accept the changes.
+    pub fn rotate(&mut self, spec: KeySpec) -> Result<&JsonWebKey, Error> {
+        let mut store = self.store.clone();
+        store.rotate(spec)?;
+        self.save(store)?;
+        Ok(self.store.current().unwrap())
+    }
+
+    /// Drops the expired retired keys, as by `KeyStore::prune`, and saves the store.
+    /// Fails like `rotate` if the file was changed by another process.
+    pub fn prune(&mut self) -> Result<(), Error> {
+        let mut store = self.store.clone();
+        store.prune();
+        self.save(store)
+    }
+
+    /// Returns whether the file was changed (or removed) since it was last read or written.
+    pub fn is_modified(&self) -> Result<bool, Error> {
+        Ok(self
+            .read()?
+            .map(|contents| Sha256::digest(&*contents).to_vec())
+            != self.digest)
+    }
+
+    /// Reads the store from the file again, discarding its state in memory.
+    pub fn reload(&mut self) -> Result<(), Error> {
+        self.store.current = None;
+        self.store.retired.clear();
+        self.digest = None;
+        if let Some(contents) = self.read()? {
+            let file: StoreFile = serde_json::from_slice(&contents)?;
+            self.store.current = file.current;
+            self.store.retired = file.retired;
+            self.digest = Some(Sha256::digest(&*contents).to_vec());
+        }
+        Ok(())
+    }
+
+    /// Returns the contents of the file, or `None` if it doesn't exist.
+    fn read(&self) -> Result<Option<Zeroizing<Vec<u8>>>, Error> {
+        match fs::read(&self.path) {
+            Ok(contents) => Ok(Some(Zeroizing::new(contents))),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save(&mut self, store: KeyStore) -> Result<(), Error> {
+        if self.is_modified()? {
+            return Err(Error::KeyStoreModified);
+        }
+        let contents = Zeroizing::new(serde_json::to_vec_pretty(&StoreFile {
+            current: store.current.clone(),
+            retired: store.retired.clone(),
+        })?);
+
+        let (temp_path, mut file) = self.create_temp_file()?;
+        let written = file
+            .write_all(&contents)
+            .and_then(|()| file.sync_all())
+            .and_then(|()| {
+                drop(file);
+                fs::rename(&temp_path, &self.path)
+            });
+        if let Err(e) = written {
+            let _ = fs::remove_file(&temp_path);
+            return Err(e.into());
+        }
+        #[cfg(unix)]
+        {
+            // Make the rename itself durable.
+            let dir = match self.path.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir,
+                _ => Path::new("."),
+            };
+            fs::File::open(dir)?.sync_all()?;
+        }
+
+        self.store = store;
+        self.digest = Some(Sha256::digest(&*contents).to_vec());
+        Ok(())
+    }
+
+    /// Creates a temporary file next to the store's file, with a random name so that concurrent
+    /// writers don't share it. An existing file (or symlink) is never opened.
+    fn create_temp_file(&self) -> io::Result<(PathBuf, fs::File)> {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        loop {
+            let mut temp_name = self.path.file_name().unwrap_or_default().to_os_string();
+            temp_name.push(format!(".{:016x}.tmp", rand::random::<u64>()));
+            let temp_path = self.path.with_file_name(temp_name);
+            match options.open(&temp_path) {
+                Ok(file) => return Ok((temp_path, file)),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+fn signing_algorithm(spec: KeySpec) -> Option<Algorithm> {
+    Some(match spec {
+        KeySpec::Symmetric(num_bits) if num_bits >= 512 => Algorithm::HS512,
+        KeySpec::Symmetric(num_bits) if num_bits >= 384 => Algorithm::HS384,
+        KeySpec::Symmetric(_) => Algorithm::HS256,
+        KeySpec::P256 => Algorithm::ES256,
+        #[cfg(feature = "crypto")]
+        KeySpec::P384 => Algorithm::ES384,
+        KeySpec::Secp256k1 => Algorithm::ES256K,
+        KeySpec::Ed25519 | KeySpec::Ed448 => Algorithm::EdDSA,
+        KeySpec::X25519 => return None,
+    })
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotate() {
+        let mut store = KeyStore::new(Duration::from_secs(3600));
+        assert!(store.current().is_none());
+        assert!(store.verification_set().keys.is_empty());
+
+        let first = store.rotate(KeySpec::P256).unwrap().clone();
+        assert_eq!(first.algorithm, Some(Algorithm::ES256));
+        assert_eq!(first.key_use, Some(KeyUse::Signing));
+        assert_eq!(first.key_id.as_deref(), Some(&*first.key.thumbprint()));
+        let second = store.rotate(KeySpec::Ed25519).unwrap().clone();
+        assert_eq!(store.current(), Some(&second));
+        assert_eq!(store.retired().len(), 1);
+        assert_eq!(store.retired()[0].jwk, first);
+
+        let jwks = store.verification_set();
+        let kids: Vec<_> = jwks.iter().map(|jwk| jwk.key_id.clone()).collect();
+        assert_eq!(kids, [second.key_id, first.key_id]);
+        assert!(jwks.iter().all(|jwk| !jwk.key.is_private()));
+
+        assert!(matches!(
+            store.rotate(KeySpec::X25519),
+            Err(Error::MismatchedAlgorithm)
+        ));
+    }
+
+    #[test]
+    fn retirement_ttl() {
+        let mut store = KeyStore::new(Duration::from_secs(0));
+        store.rotate(KeySpec::Symmetric(256)).unwrap();
+        assert!(store.verification_set().keys.is_empty());
+        store.rotate(KeySpec::P256).unwrap();
+        assert!(store.retired().is_empty());
+        assert_eq!(store.verification_set().keys.len(), 1);
+
+        store.set_retirement_ttl(Duration::from_secs(60));
+        store.rotate(KeySpec::P256).unwrap();
+        store.prune();
+        assert_eq!(store.retired().len(), 1);
+        assert_eq!(store.verification_set().keys.len(), 2);
+    }
+
+    #[test]
+    fn file_key_store() {
+        let dir = std::env::temp_dir().join(format!("jsonwebkey-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("keys.json");
+        let _ = fs::remove_file(&path);
+
+        let mut file_store = FileKeyStore::open(&path, Duration::from_secs(3600)).unwrap();
+        assert!(file_store.store().current().is_none());
+        assert!(!path.exists());
+        let first = file_store.rotate(KeySpec::P256).unwrap().clone();
+        let second = file_store.rotate(KeySpec::Ed25519).unwrap().clone();
+        assert!(!file_store.is_modified().unwrap());
+        // No temporary files are left behind.
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let reopened = FileKeyStore::open(&path, Duration::from_secs(3600)).unwrap();
+        assert_eq!(reopened.store(), file_store.store());
+        assert_eq!(reopened.store().current(), Some(&second));
+        assert_eq!(reopened.store().retired()[0].jwk, first);
+
+        let mut other = reopened;
+        other.rotate(KeySpec::P256).unwrap();
+        assert!(file_store.is_modified().unwrap());
+        assert!(matches!(
+            file_store.rotate(KeySpec::P256),
+            Err(Error::KeyStoreModified)
+        ));
+        assert_eq!(file_store.store().current(), Some(&second));
+        file_store.reload().unwrap();
+        assert_eq!(file_store.store(), other.store());
+        file_store.prune().unwrap();
+
+        // A symlink at the old, fixed temporary path isn't followed.
+        #[cfg(unix)]
+        {
+            let target = dir.join("target");
+            std::os::unix::fs::symlink(&target, dir.join("keys.json.tmp")).unwrap();
+            file_store.rotate(KeySpec::P256).unwrap();
+            assert!(!target.exists());
+        }
+
+        fs::write(&path, "{").unwrap();
+        assert!(matches!(file_store.reload(), Err(Error::Serde(_))));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,115 @@
+use std::fmt;
+
+use crate::{JsonWebKey, Key};
+
+/// A scheme for assigning the `kid` of a key, so that keys get consistent identifiers.
+pub enum KidStrategy {
+    /// A random (version 4) UUID.
+    #[cfg(feature = "generate")]
+    UuidV4,
+    /// The RFC 7638 SHA-256 thumbprint of the key.
+    Thumbprint,
+    /// The first characters of the RFC 7638 SHA-256 thumbprint of the key.
+    TruncatedThumbprint(usize),
+    /// The result of a custom function of the key.
+    Custom(Box<dyn Fn(&Key) -> String + Send + Sync>),
+}
+
+impl KidStrategy {
+    /// Returns a `kid` for `key` according to this strategy.
+    pub fn kid_for(&self, key: &Key) -> String {
+        match self {
+            #[cfg(feature = "generate")]
+            Self::UuidV4 => {
+                use rand::RngCore;
+
+                let mut uuid = [0u8; 16];
+                rand::thread_rng().fill_bytes(&mut uuid);
+                uuid[6] = (uuid[6] & 0x0f) | 0x40; // version 4
+                uuid[8] = (uuid[8] & 0x3f) | 0x80; // RFC 4122 variant
+                let hex: String = uuid.iter().map(|b| format!("{:02x}", b)).collect();
+                format!(
+                    "{}-{}-{}-{}-{}",
+                    &hex[..8],
+                    &hex[8..12],
+                    &hex[12..16],
+                    &hex[16..20],
+                    &hex[20..]
+                )
+            }
+            Self::Thumbprint => key.thumbprint(),
+            Self::TruncatedThumbprint(len) => {
+                let mut thumbprint = key.thumbprint();
+                thumbprint.truncate(*len);
+                thumbprint
+            }
+            Self::Custom(kid_for) => kid_for(key),
+        }
+    }
+}
+
+impl fmt::Debug for KidStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "generate")]
+            Self::UuidV4 => write!(f, "UuidV4"),
+            Self::Thumbprint => write!(f, "Thumbprint"),
+            Self::TruncatedThumbprint(len) => write!(f, "TruncatedThumbprint({})", len),
+            Self::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+impl JsonWebKey {
+    /// Sets the `kid` of this key according to `strategy`.
+    pub fn set_kid_with(&mut self, strategy: &KidStrategy) {
+        self.key_id = Some(strategy.kid_for(&self.key));
+    }
+
+    /// Returns this key with its `kid` set according to `strategy`, e.g.
+    /// `JsonWebKey::new(Key::generate_p256()).with_kid(&KidStrategy::Thumbprint)`.
+    pub fn with_kid(mut self, strategy: &KidStrategy) -> Self {
+        self.set_kid_with(strategy);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key() -> Key {
+        Key::Symmetric {
+            key: vec![42; 32].into(),
+        }
+    }
+
+    #[test]
+    fn thumbprint_strategies() {
+        let jwk = JsonWebKey::new(key()).with_kid(&KidStrategy::Thumbprint);
+        assert_eq!(jwk.key_id, Some(key().thumbprint()));
+
+        let jwk = JsonWebKey::new(key()).with_kid(&KidStrategy::TruncatedThumbprint(8));
+        assert_eq!(jwk.key_id.as_deref(), Some(&key().thumbprint()[..8]));
+    }
+
+    #[test]
+    fn custom_strategy() {
+        let strategy = KidStrategy::Custom(Box::new(|key| format!("key-{}", key.num_bits())));
+        let mut jwk = JsonWebKey::new(key());
+        jwk.set_kid_with(&strategy);
+        assert_eq!(jwk.key_id.as_deref(), Some("key-256"));
+        assert_eq!(format!("{:?}", strategy), "Custom(..)");
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn uuid_v4_strategy() {
+        let kid = KidStrategy::UuidV4.kid_for(&key());
+        assert_eq!(kid.len(), 36);
+        assert_eq!(kid.matches('-').count(), 4);
+        assert_eq!(&kid[14..15], "4");
+        assert!(matches!(&kid[19..20], "8" | "9" | "a" | "b"));
+        assert_ne!(kid, KidStrategy::UuidV4.kid_for(&key()));
+    }
+}
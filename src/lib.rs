@@ -27,6 +27,7 @@
 //! #[cfg(all(feature = "generate", feature = "jwt-convert"))] {
 //! extern crate jsonwebtoken as jwt;
 //! extern crate jsonwebkey as jwk;
+//! use std::convert::TryFrom;
 //!
 //! #[derive(serde::Serialize, serde::Deserialize)]
 //! struct TokenClaims {}
@@ -34,7 +35,7 @@
 //! let mut my_jwk = jwk::JsonWebKey::new(jwk::Key::generate_p256());
 //! my_jwk.set_algorithm(jwk::Algorithm::ES256);
 //!
-//! let alg: jwt::Algorithm = my_jwk.algorithm.unwrap().into();
+//! let alg = jwt::Algorithm::try_from(my_jwk.algorithm.unwrap()).unwrap();
 //! let token = jwt::encode(
 //!     &jwt::Header::new(alg),
 //!     &TokenClaims {},
@@ -49,18 +50,59 @@
 //!
 //! ## Features
 //!
-//! * `convert` - enables `Key::{to_der, to_pem}`.
-//!               This pulls in the [yasna](https://crates.io/crates/yasna) crate.
-//! * `generate` - enables `Key::{generate_p256, generate_symmetric}`.
-//!                This pulls in the [p256](https://crates.io/crates/p256) and [rand](https://crates.io/crates/rand) crates.
+//! * `convert` - enables `Key::{from_der, from_pem, to_der, to_pem}`.
+//!   This pulls in the [yasna](https://crates.io/crates/yasna) crate.
+//! * `generate` - enables `Key::{generate_p256, generate_secp256k1, generate_ed25519, generate_x25519,
+//!   generate_ed448, generate_symmetric}`, `JsonWebKey::generate` (also for
+//!   batches of keys), the `KeyStore` key rotation manager (also backed by a
+//!   file, as `FileKeyStore`), and `Key::generate_p384` together with `crypto`.
+//!   This pulls in the [p256](https://crates.io/crates/p256), [k256](https://crates.io/crates/k256),
+//!   [ed448-rust](https://crates.io/crates/ed448-rust), [rand](https://crates.io/crates/rand),
+//!   [ring](https://crates.io/crates/ring), and [x25519-dalek](https://crates.io/crates/x25519-dalek) crates.
 //! * `jsonwebtoken` - enables conversions to types in the [jsonwebtoken](https://crates.io/crates/jsonwebtoken) crate.
+//! * `did-key` - enables `Key::{to_did_key, from_did_key}`.
+//!   This pulls in the [p256](https://crates.io/crates/p256) crate.
+//! * `crypto` - enables `JsonWebKey::{generate_self_signed_cert, set_certificate_chain}` and
+//!   `Key::{sign, verify, derive_shared_secret, wrap_key, unwrap_key,
+//!   from_encrypted_pkcs8_der, from_encrypted_pkcs8_pem, try_to_encrypted_pkcs8_pem}`.
+//!   It also enables `JsonWebKey::{seal_with_password, unseal_with_password}` and the
+//!   `jwe`, `jws`, and `jwt` modules.
+//!   This pulls in the [ring](https://crates.io/crates/ring), [aes](https://crates.io/crates/aes),
+//!   [aes-kw](https://crates.io/crates/aes-kw), [cbc](https://crates.io/crates/cbc), [p256](https://crates.io/crates/p256), and
+//!   [x25519-dalek](https://crates.io/crates/x25519-dalek) crates.
+//! * `cose` - enables `Key::{to_cose_key, from_cose_key}`.
 
-#[macro_use]
+#[cfg_attr(test, macro_use)]
 extern crate generic_array;
 
 mod byte_array;
 mod byte_vec;
+mod certificate;
+mod confirmation;
+#[cfg(feature = "cose")]
+mod cose;
+pub mod dpop;
+#[cfg(feature = "crypto")]
+mod ecdh;
+pub mod ecdsa;
+#[cfg(feature = "generate")]
+mod generate;
+#[cfg(feature = "crypto")]
+pub mod jwe;
+#[cfg(feature = "crypto")]
+pub mod jws;
+#[cfg(feature = "crypto")]
+pub mod jwt;
+#[cfg(feature = "crypto")]
+mod key_management;
 mod key_ops;
+mod key_set;
+#[cfg(feature = "generate")]
+mod key_store;
+mod kid;
+mod openssh;
+#[cfg(feature = "crypto")]
+mod sign;
 #[cfg(test)]
 mod tests;
 mod utils;
@@ -71,9 +113,17 @@ use serde::{Deserialize, Serialize};
 
 pub use byte_array::ByteArray;
 pub use byte_vec::ByteVec;
+pub use certificate::Base64DerCertificate;
+pub use confirmation::Confirmation;
+#[cfg(feature = "generate")]
+pub use generate::{JsonWebKeyBuilder, KeySpec};
 pub use key_ops::KeyOps;
+pub use key_set::{JsonWebKeySet, KeyResolver};
+#[cfg(feature = "generate")]
+pub use key_store::{FileKeyStore, KeyStore, RetiredKey};
+pub use kid::KidStrategy;
 
-use generic_array::typenum::U32;
+use generic_array::typenum::{U32, U48, U56, U57, U64, U66};
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct JsonWebKey {
@@ -91,6 +141,26 @@ pub struct JsonWebKey {
 
     #[serde(default, rename = "alg", skip_serializing_if = "Option::is_none")]
     pub algorithm: Option<Algorithm>,
+
+    /// Names of members which must be understood and processed by users of this key.
+    #[serde(default, rename = "crit", skip_serializing_if = "Option::is_none")]
+    pub critical: Option<Vec<String>>,
+
+    /// The URL of the X.509 certificate chain of this key, in PEM format.
+    #[serde(default, rename = "x5u", skip_serializing_if = "Option::is_none")]
+    pub certificate_url: Option<String>,
+
+    /// The X.509 certificate chain of this key, starting with the certificate containing it.
+    #[serde(default, rename = "x5c", skip_serializing_if = "Option::is_none")]
+    pub certificate_chain: Option<Vec<Base64DerCertificate>>,
+
+    /// The base64url-encoded SHA-1 digest of the DER encoding of this key's X.509 certificate.
+    #[serde(default, rename = "x5t", skip_serializing_if = "Option::is_none")]
+    pub certificate_sha1_thumbprint: Option<String>,
+
+    /// The base64url-encoded SHA-256 digest of the DER encoding of this key's X.509 certificate.
+    #[serde(default, rename = "x5t#S256", skip_serializing_if = "Option::is_none")]
+    pub certificate_sha256_thumbprint: Option<String>,
 }
 
 impl JsonWebKey {
@@ -101,20 +171,297 @@ impl JsonWebKey {
             key_ops: KeyOps::empty(),
             key_id: None,
             algorithm: None,
+            critical: None,
+            certificate_url: None,
+            certificate_chain: None,
+            certificate_sha1_thumbprint: None,
+            certificate_sha256_thumbprint: None,
         }
     }
 
     pub fn set_algorithm(&mut self, alg: Algorithm) -> Result<(), Error> {
-        Self::validate_algorithm(alg, &*self.key)?;
+        Self::validate_algorithm(&alg, &self.key)?;
         self.algorithm = Some(alg);
         Ok(())
     }
 
+    /// Sets the `kid` to the RFC 7638 SHA-256 thumbprint of the key, a deterministic identifier
+    /// which is the same for the private key and its public part.
+    pub fn set_kid_to_thumbprint(&mut self) {
+        self.key_id = Some(self.key.thumbprint());
+    }
+
+    /// Returns this key with its `kid` set as by `set_kid_to_thumbprint`.
+    pub fn with_kid_from_thumbprint(mut self) -> Self {
+        self.set_kid_to_thumbprint();
+        self
+    }
+
+    /// Serializes this key using the JSON Canonicalization Scheme of
+    /// [RFC 8785](https://tools.ietf.org/html/rfc8785): members sorted by name, with no
+    /// insignificant whitespace. Unlike `to_string`, the output doesn't depend on the order in
+    /// which this crate happens to serialize members, so it may be hashed or signed.
+    pub fn to_canonical_json(&self) -> String {
+        utils::jcs::to_string(&serde_json::to_value(self).unwrap())
+    }
+
+    /// Sets the `x5t` and `x5t#S256` members to the digests of the first certificate of the `x5c`
+    /// chain. Returns false, leaving them untouched, if there is no such certificate.
+    pub fn set_certificate_thumbprints(&mut self) -> bool {
+        use sha2::Digest;
+
+        use crate::utils::base64_encode_unpadded as b64;
+
+        let cert = match self
+            .certificate_chain
+            .as_ref()
+            .and_then(|chain| chain.first())
+        {
+            Some(cert) => cert,
+            None => return false,
+        };
+        self.certificate_sha1_thumbprint = Some(b64(sha1::Sha1::digest(&cert[..])));
+        self.certificate_sha256_thumbprint = Some(b64(sha2::Sha256::digest(&cert[..])));
+        true
+    }
+
+    /// Checks that the first certificate of the `x5c` chain, if any, contains this key, as
+    /// required by [RFC 7517 §4.7](https://tools.ietf.org/html/rfc7517#section-4.7).
+    /// Keys without an `x5c` member trivially pass.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn verify_x5c_binding(&self) -> Result<(), Error> {
+        let leaf = match self
+            .certificate_chain
+            .as_ref()
+            .and_then(|chain| chain.first())
+        {
+            Some(leaf) => leaf,
+            None => return Ok(()),
+        };
+        let public_key_info =
+            utils::x509::read_public_key_info(leaf).map_err(ConversionError::from)?;
+        if !Key::from_spki_der(&public_key_info)?.public_eq(&self.key) {
+            return Err(Error::CertificateMismatch);
+        }
+        Ok(())
+    }
+
+    /// Returns the public key contained in a DER-encoded X.509 certificate, with the certificate
+    /// as its `x5c` chain and the certificate's SHA-256 digest as its `x5t#S256`.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn from_certificate_der(der: &[u8]) -> Result<Self, ConversionError> {
+        Self::from_certificate_chain(vec![der.to_vec().into()])
+    }
+
+    /// Like `from_certificate_der`, but for the `CERTIFICATE` blocks of `pem`, which must start
+    /// with the certificate of the key and all go into the `x5c` chain.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn from_certificate_pem(pem: &str) -> Result<Self, ConversionError> {
+        let chain = utils::pem::decode_certificates(pem)?;
+        Self::from_certificate_chain(chain.into_iter().map(Into::into).collect())
+    }
+
+    #[cfg(feature = "pkcs-convert")]
+    fn from_certificate_chain(chain: Vec<Base64DerCertificate>) -> Result<Self, ConversionError> {
+        use sha2::Digest;
+
+        let leaf = chain.first().ok_or(ConversionError::InvalidPem)?;
+        let mut jwk = Self::new(Key::from_certificate_der(leaf)?);
+        jwk.certificate_sha256_thumbprint = Some(utils::base64_encode_unpadded(
+            sha2::Sha256::digest(&leaf[..]),
+        ));
+        jwk.certificate_chain = Some(chain);
+        Ok(jwk)
+    }
+
+    /// Returns this key with only its key material and `kid`, so that it may be re-annotated
+    /// for another purpose without carrying over stale `alg`, `use`, `key_ops`, or `crit` values.
+    pub fn without_metadata(self) -> Self {
+        Self {
+            key_id: self.key_id,
+            ..Self::new(*self.key)
+        }
+    }
+
+    /// Returns the family of this key's `alg` or, if unset, the family implied by its key type.
+    pub fn algorithm_family(&self) -> AlgorithmFamily {
+        match (&self.algorithm, &*self.key) {
+            (Some(alg), _) => alg.family(),
+            (None, Key::Symmetric { .. }) => AlgorithmFamily::Hmac,
+            (None, Key::RSA { .. }) => AlgorithmFamily::Rsa,
+            (None, Key::EC { .. }) => AlgorithmFamily::Ecdsa,
+            (None, Key::OKP { curve }) if curve.is_key_agreement() => AlgorithmFamily::EcdhEs,
+            (None, Key::OKP { .. }) => AlgorithmFamily::Eddsa,
+            (None, Key::Unknown { .. }) => AlgorithmFamily::Unknown,
+        }
+    }
+
+    /// Returns a deterministic, multi-line description of this key, suitable for inclusion in
+    /// interoperability bug reports. Private key material is never included.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn debug_report(&self) -> String {
+        use std::fmt::Write;
+
+        let mut report = String::new();
+        let kty = match &*self.key {
+            Key::EC { .. } => "EC",
+            Key::RSA { .. } => "RSA",
+            Key::Symmetric { .. } => "oct",
+            Key::OKP { .. } => "OKP",
+            Key::Unknown { kty, .. } => kty,
+        };
+        writeln!(report, "kty: {}", kty).unwrap();
+        match &*self.key {
+            Key::EC { curve } => writeln!(report, "crv: {}", curve.name()).unwrap(),
+            Key::OKP { curve } => writeln!(report, "crv: {}", curve.name()).unwrap(),
+            _ => {}
+        }
+        let none = "(none)".to_string();
+        let kid = self.key_id.as_ref().unwrap_or(&none);
+        writeln!(report, "kid: {}", kid).unwrap();
+        let alg = self.algorithm.as_ref().map(|alg| format!("{:?}", alg));
+        writeln!(report, "alg: {}", alg.as_ref().unwrap_or(&none)).unwrap();
+        writeln!(report, "private: {}", self.key.is_private()).unwrap();
+        writeln!(report, "bits: {}", self.key.num_bits()).unwrap();
+        writeln!(report, "thumbprint: {}", self.key.thumbprint()).unwrap();
+        match self.key.try_to_der() {
+            Ok(der) => writeln!(report, "der length: {}", der.len()).unwrap(),
+            Err(e) => writeln!(report, "der length: n/a ({})", e).unwrap(),
+        }
+        match self.key.to_public().map(|public| public.try_to_pem()) {
+            Some(Ok(pem)) => write!(report, "public pem:\n{}", pem).unwrap(),
+            Some(Err(e)) => writeln!(report, "public pem: n/a ({})", e).unwrap(),
+            None => writeln!(report, "public pem: n/a (symmetric key)").unwrap(),
+        }
+        report
+    }
+
+    /// Copies the `use`, `key_ops`, `kid`, and `alg` members of `other` onto this key, leaving
+    /// the key material untouched. Nothing is copied if `other`'s `alg` is unsuitable for this key.
+    pub fn apply_metadata_from(&mut self, other: &JsonWebKey) -> Result<(), Error> {
+        if let Some(alg) = &other.algorithm {
+            Self::validate_algorithm(alg, &self.key)?;
+        }
+        self.key_use = other.key_use;
+        self.key_ops = other.key_ops;
+        self.key_id = other.key_id.clone();
+        self.algorithm = other.algorithm.clone();
+        Ok(())
+    }
+
+    /// Returns the names listed in the `crit` member, if any.
+    pub fn critical_params(&self) -> &[String] {
+        self.critical.as_deref().unwrap_or_default()
+    }
+
+    /// Returns true iff this key may be used to produce signatures or MACs, as determined by
+    /// its type, private components, `use`, `key_ops`, and `alg`.
+    pub fn can_sign(&self) -> bool {
+        self.key.is_private()
+            && !matches!(
+                self.algorithm_family(),
+                AlgorithmFamily::EcdhEs | AlgorithmFamily::Unknown
+            )
+            && self.key_use != Some(KeyUse::Encryption)
+            && (self.key_ops.is_empty() || self.key_ops.contains(KeyOps::SIGN))
+            && self
+                .algorithm
+                .as_ref()
+                .map(Algorithm::is_signing)
+                .unwrap_or(true)
+    }
+
+    /// Returns true iff this key may be used to encrypt (or wrap or derive) keys or content, as
+    /// determined by its type, `use`, `key_ops`, and `alg`.
+    pub fn can_encrypt(&self) -> bool {
+        let encryption_ops =
+            KeyOps::ENCRYPT | KeyOps::WRAP_KEY | KeyOps::DERIVE_KEY | KeyOps::DERIVE_BITS;
+        // Symmetric keys without an `alg` default to the HMAC family but may equally be used for
+        // key wrapping, so only an explicit signing `alg` (HS*, PS*, etc.) rules them out, below.
+        !matches!(
+            self.algorithm_family(),
+            AlgorithmFamily::Eddsa | AlgorithmFamily::Unknown
+        ) && self.key_use != Some(KeyUse::Signing)
+            && (self.key_ops.is_empty() || self.key_ops.intersects(encryption_ops))
+            && self
+                .algorithm
+                .as_ref()
+                .map(|alg| !alg.is_signing())
+                .unwrap_or(true)
+    }
+
     pub fn from_slice(bytes: impl AsRef<[u8]>) -> Result<Self, Error> {
         Ok(serde_json::from_slice(bytes.as_ref())?)
     }
 
-    fn validate_algorithm(alg: Algorithm, key: &Key) -> Result<(), Error> {
+    /// Parses a JWK like `from_str`, but accepting the dialects of other producers such as
+    /// `SubtleCrypto.exportKey("jwk", ..)`: binary members may use padded or standard (not
+    /// URL-safe) base64, and unrecognized `key_ops` are dropped instead of being rejected.
+    /// As with `from_str`, extra members like WebCrypto's `ext` are ignored.
+    pub fn from_str_lenient(json: &str) -> Result<Self, Error> {
+        const BINARY_MEMBERS: &[&str] = &["k", "x", "y", "d", "n", "e", "p", "q", "dp", "dq", "qi"];
+
+        let mut value: serde_json::Value = serde_json::from_str(json)?;
+        if let Some(members) = value.as_object_mut() {
+            for member in BINARY_MEMBERS {
+                if let Some(serde_json::Value::String(b64)) = members.get_mut(*member) {
+                    *b64 = b64
+                        .trim_end_matches('=')
+                        .replace('+', "-")
+                        .replace('/', "_");
+                }
+            }
+            if let Some(serde_json::Value::Array(key_ops)) = members.get_mut("key_ops") {
+                key_ops.retain(|op| {
+                    serde_json::from_value::<KeyOps>(serde_json::Value::Array(vec![op.clone()]))
+                        .is_ok()
+                });
+            }
+        }
+        let jwk: Self = serde_json::from_value(value)?;
+        if let Some(alg) = &jwk.algorithm {
+            Self::validate_algorithm(alg, &jwk.key)?;
+        }
+        Ok(jwk)
+    }
+
+    /// Returns the [`did:jwk`](https://github.com/quartzjer/did-jwk/blob/main/spec.md) identifier
+    /// of the public part of this key, which keeps its metadata.
+    pub fn to_did_jwk(&self) -> Result<String, ConversionError> {
+        let public = match &*self.key {
+            Key::Symmetric { .. } => return Err(ConversionError::NotAsymmetric),
+            key => key.to_public().ok_or(ConversionError::UnsupportedKey)?,
+        };
+        let jwk = Self {
+            key: Box::new(public.into_owned()),
+            ..self.clone()
+        };
+        let json = serde_json::to_string(&jwk).unwrap();
+        Ok(format!(
+            "did:jwk:{}",
+            crate::utils::base64_encode_unpadded(json)
+        ))
+    }
+
+    /// Parses a `did:jwk` identifier, or a DID URL thereof such as `did:jwk:...#0`.
+    pub fn from_did_jwk(did: &str) -> Result<Self, Error> {
+        let encoded = did
+            .strip_prefix("did:jwk:")
+            .ok_or(Error::InvalidDidJwk)?
+            .split('#')
+            .next()
+            .unwrap();
+        let json = base64::decode_config(encoded, base64::URL_SAFE_NO_PAD)?;
+        let jwk: Self = std::str::from_utf8(&json)
+            .map_err(|_| Error::InvalidDidJwk)?
+            .parse()?;
+        if jwk.key.is_private() {
+            return Err(Error::InvalidDidJwk);
+        }
+        Ok(jwk)
+    }
+
+    fn validate_algorithm(alg: &Algorithm, key: &Key) -> Result<(), Error> {
         use Algorithm::*;
         use Key::*;
         match (alg, key) {
@@ -124,23 +471,102 @@ impl JsonWebKey {
                     curve: Curve::P256 { .. },
                 },
             )
+            | (
+                ES384,
+                EC {
+                    curve: Curve::P384 { .. },
+                },
+            )
+            | (
+                ES512,
+                EC {
+                    curve: Curve::P521 { .. },
+                },
+            )
+            | (
+                ES256K,
+                EC {
+                    curve: Curve::Secp256k1 { .. },
+                },
+            )
+            | (
+                EdDSA,
+                OKP {
+                    curve: OkpCurve::Ed25519 { .. },
+                },
+            )
+            | (
+                EdDSA,
+                OKP {
+                    curve: OkpCurve::Ed448 { .. },
+                },
+            )
             | (RS256, RSA { .. })
-            | (HS256, Symmetric { .. }) => Ok(()),
+            | (RS384, RSA { .. })
+            | (RS512, RSA { .. })
+            | (PS256, RSA { .. })
+            | (PS384, RSA { .. })
+            | (PS512, RSA { .. })
+            | (HS256, Symmetric { .. })
+            | (HS384, Symmetric { .. })
+            | (HS512, Symmetric { .. })
+            | (RSA1_5, RSA { .. })
+            | (RsaOaep, RSA { .. })
+            | (RsaOaep256, RSA { .. })
+            | (Dir, Symmetric { .. })
+            | (Pbes2Hs256A128KW, Symmetric { .. })
+            | (Pbes2Hs384A192KW, Symmetric { .. })
+            | (Pbes2Hs512A256KW, Symmetric { .. }) => Ok(()),
+            (A128KW, Symmetric { key }) | (A128GCMKW, Symmetric { key }) if key.len() == 16 => {
+                Ok(())
+            }
+            (A192KW, Symmetric { key }) | (A192GCMKW, Symmetric { key }) if key.len() == 24 => {
+                Ok(())
+            }
+            (A256KW, Symmetric { key }) | (A256GCMKW, Symmetric { key }) if key.len() == 32 => {
+                Ok(())
+            }
+            (Other(_), _) => Ok(()),
+            (alg, Symmetric { key }) if alg.content_key_len() == Some(key.len()) => Ok(()),
+            (EcdhEs, EC { curve })
+            | (EcdhEsA128KW, EC { curve })
+            | (EcdhEsA192KW, EC { curve })
+            | (EcdhEsA256KW, EC { curve })
+                if !matches!(curve, Curve::Other { .. }) =>
+            {
+                Ok(())
+            }
+            (EcdhEs, OKP { curve })
+            | (EcdhEsA128KW, OKP { curve })
+            | (EcdhEsA192KW, OKP { curve })
+            | (EcdhEsA256KW, OKP { curve })
+                if curve.is_key_agreement() =>
+            {
+                Ok(())
+            }
             _ => Err(Error::MismatchedAlgorithm),
         }
     }
 }
 
+/// Hashes the key material only, so that keys may be used in `HashMap`s and `HashSet`s.
+impl std::hash::Hash for JsonWebKey {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.key.hash(state);
+    }
+}
+
 impl std::str::FromStr for JsonWebKey {
     type Err = Error;
     fn from_str(json: &str) -> Result<Self, Self::Err> {
         let jwk = Self::from_slice(json.as_bytes())?;
 
-        let alg = match jwk.algorithm {
+        let alg = match &jwk.algorithm {
             Some(alg) => alg,
             None => return Ok(jwk),
         };
-        Self::validate_algorithm(alg, &*jwk.key).map(|_| jwk)
+        Self::validate_algorithm(alg, &jwk.key)?;
+        Ok(jwk)
     }
 }
 
@@ -155,7 +581,7 @@ impl std::fmt::Display for JsonWebKey {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(tag = "kty")]
+#[serde(tag = "kty", remote = "Self")]
 pub enum Key {
     /// An elliptic curve, as per [RFC 7518 §6.2](https://tools.ietf.org/html/rfc7518#section-6.2).
     EC {
@@ -176,242 +602,1648 @@ pub enum Key {
         #[serde(rename = "k")]
         key: ByteVec,
     },
+    /// An octet key pair, as per [RFC 8037 §2](https://tools.ietf.org/html/rfc8037#section-2).
+    OKP {
+        #[serde(flatten)]
+        curve: OkpCurve,
+    },
+    /// A key of a type which is not supported by this crate, kept verbatim so that it can be
+    /// round-tripped or filtered out. Such keys are not valid for any algorithm.
+    #[serde(skip)]
+    Unknown {
+        /// The value of the `kty` member.
+        kty: String,
+        /// The remaining key-specific members.
+        members: serde_json::Map<String, serde_json::Value>,
+    },
+}
+
+impl Serialize for Key {
+    fn serialize<S: serde::ser::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+        match self {
+            Self::Unknown { kty, members } => {
+                let mut map = s.serialize_map(Some(members.len() + 1))?;
+                map.serialize_entry("kty", kty)?;
+                for (name, value) in members {
+                    map.serialize_entry(name, value)?;
+                }
+                map.end()
+            }
+            _ => Self::serialize(self, s),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D: serde::de::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        use serde::de::Error as _;
+        let mut members = serde_json::Map::deserialize(d)?;
+        let kty = match members.get("kty") {
+            Some(serde_json::Value::String(kty)) => kty.clone(),
+            Some(_) => return Err(D::Error::custom("`kty` must be a string")),
+            None => return Err(D::Error::missing_field("kty")),
+        };
+        if matches!(kty.as_str(), "EC" | "RSA" | "oct" | "OKP") {
+            Self::deserialize(serde_json::Value::Object(members)).map_err(D::Error::custom)
+        } else {
+            members.remove("kty");
+            Ok(Self::Unknown { kty, members })
+        }
+    }
+}
+
+/// Hashes the thumbprint, so that a private key and its public part have the same hash.
+impl std::hash::Hash for Key {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.thumbprint().hash(state);
+    }
 }
 
 impl Key {
+    /// Makes an EC key from the raw affine coordinates of its public point and, optionally,
+    /// its private scalar. The coordinates must have the size of the curve's field elements,
+    /// and the scalar may be at most that size.
+    pub fn ec_from_coordinates(
+        crv: &str,
+        x: &[u8],
+        y: &[u8],
+        d: Option<&[u8]>,
+    ) -> Result<Self, ConversionError> {
+        if !Curve::is_supported(crv) {
+            return Err(ConversionError::UnsupportedKey);
+        }
+        Ok(Self::EC {
+            curve: Curve::from_components(crv, d, x, y).map_err(ConversionError::InvalidLength)?,
+        })
+    }
+
+    /// Makes an OKP key from its raw public key and, optionally, its raw private key
+    /// ([RFC 8037 §2](https://tools.ietf.org/html/rfc8037#section-2)), whose sizes must match
+    /// those of the curve.
+    pub fn okp_from_raw(
+        crv: &str,
+        public: &[u8],
+        secret: Option<&[u8]>,
+    ) -> Result<Self, ConversionError> {
+        if !OkpCurve::is_supported(crv) {
+            return Err(ConversionError::UnsupportedKey);
+        }
+        Ok(Self::OKP {
+            curve: OkpCurve::from_components(crv, secret, public)
+                .map_err(ConversionError::InvalidLength)?,
+        })
+    }
+
+    /// Returns the raw public key material of this asymmetric key, as consumed by low-level
+    /// crypto APIs: the uncompressed SEC1 point of an EC key, the public key of an OKP key,
+    /// or the modulus and exponent of an RSA key.
+    pub fn to_raw_public_bytes(&self) -> Result<RawPublicKey, ConversionError> {
+        Ok(match self {
+            Self::EC { curve } => {
+                let (_d, x, y) = curve.components().ok_or(ConversionError::UnsupportedKey)?;
+                RawPublicKey::Ec([&[0x04 /* uncompressed */][..], x, y].concat())
+            }
+            Self::OKP { curve } => RawPublicKey::Okp(curve.components().1.to_vec()),
+            Self::RSA { public, .. } => RawPublicKey::Rsa {
+                n: public.n.to_vec(),
+                e: vec![0x01, 0x00, 0x01],
+            },
+            Self::Symmetric { .. } => return Err(ConversionError::NotAsymmetric),
+            Self::Unknown { .. } => return Err(ConversionError::UnsupportedKey),
+        })
+    }
+
     /// Returns true iff this key only contains private components (i.e. a private asymmetric
-    /// key or a symmetric key).
+    /// key or a symmetric key). Keys of unknown type are conservatively considered private.
     fn is_private(&self) -> bool {
         match self {
-            Self::Symmetric { .. }
-            | Self::EC {
-                curve: Curve::P256 { d: Some(_), .. },
-                ..
-            }
-            | Self::RSA {
-                private: Some(_), ..
-            } => true,
-            _ => false,
+            Self::Symmetric { .. } | Self::Unknown { .. } => true,
+            Self::EC { curve } => curve.is_private(),
+            Self::RSA { private, .. } => private.is_some(),
+            Self::OKP { curve } => curve.components().0.is_some(),
         }
     }
 
-    /// Returns the public part of this key (symmetric keys have no public parts).
-    pub fn to_public(&self) -> Option<Cow<Self>> {
+    /// Returns the public part of this key (symmetric keys and keys of unknown type have no
+    /// known public parts).
+    pub fn to_public(&self) -> Option<Cow<'_, Self>> {
         if !self.is_private() {
             return Some(Cow::Borrowed(self));
         }
         Some(Cow::Owned(match self {
-            Self::Symmetric { .. } => return None,
-            Self::EC {
-                curve: Curve::P256 { x, y, .. },
-            } => Self::EC {
-                curve: Curve::P256 {
-                    x: x.clone(),
-                    y: y.clone(),
-                    d: None,
-                },
+            Self::Symmetric { .. } | Self::Unknown { .. } => return None,
+            Self::EC { curve } => Self::EC {
+                curve: curve.to_public(),
             },
             Self::RSA { public, .. } => Self::RSA {
                 public: public.clone(),
                 private: None,
             },
+            Self::OKP { curve } => Self::OKP {
+                curve: curve.to_public(),
+            },
         }))
     }
 
+    /// Returns the size of this key in bits: the curve size for EC and OKP keys, the modulus
+    /// size for RSA keys, the key length for symmetric keys, and zero for unknown key types.
+    pub fn num_bits(&self) -> usize {
+        match self {
+            Self::EC { curve } => curve.num_bits(),
+            Self::OKP { curve } => curve.num_bits(),
+            Self::RSA { public, .. } => {
+                let n = public.n.as_slice();
+                match n.iter().position(|&b| b != 0) {
+                    Some(i) => (n.len() - i) * 8 - n[i].leading_zeros() as usize,
+                    None => 0,
+                }
+            }
+            Self::Symmetric { key } => key.len() * 8,
+            Self::Unknown { .. } => 0,
+        }
+    }
+
+    /// Returns the [RFC 7638](https://tools.ietf.org/html/rfc7638) SHA-256 thumbprint of this key,
+    /// encoded as unpadded base64url. Private keys have the same thumbprint as their public part.
+    /// As their required members are unknown, keys of unknown type are hashed using all members.
+    pub fn thumbprint(&self) -> String {
+        self.thumbprint_with(HashAlg::Sha256)
+    }
+
+    /// Returns the [`did:key`](https://w3c-ccg.github.io/did-method-key/) identifier of the
+    /// public part of this key. Only P-256, Ed25519, and X25519 keys are supported.
+    #[cfg(feature = "did-key")]
+    pub fn to_did_key(&self) -> Result<String, ConversionError> {
+        let (crv, public) = match self {
+            Self::EC {
+                curve: Curve::P256 { x, y, .. },
+            } => {
+                // The SEC 1 compressed point.
+                let mut point = vec![0x02 | (y[31] & 1)];
+                point.extend_from_slice(x);
+                ("P-256", point)
+            }
+            Self::OKP { curve } => (curve.name(), curve.components().1.to_vec()),
+            Self::Symmetric { .. } => return Err(ConversionError::NotAsymmetric),
+            _ => return Err(ConversionError::UnsupportedKey),
+        };
+        let (_, codec) = DID_KEY_CODECS
+            .iter()
+            .find(|(name, _)| *name == crv)
+            .ok_or(ConversionError::UnsupportedKey)?;
+        let bytes = [codec, &public[..]].concat();
+        Ok(format!("did:key:z{}", crate::utils::base58::encode(&bytes)))
+    }
+
+    /// Parses a `did:key` identifier, or a DID URL thereof such as `did:key:z...#z...`.
+    #[cfg(feature = "did-key")]
+    pub fn from_did_key(did: &str) -> Result<Self, Error> {
+        let encoded = did
+            .strip_prefix("did:key:z")
+            .ok_or(Error::InvalidDidKey)?
+            .split('#')
+            .next()
+            .unwrap();
+        let bytes = crate::utils::base58::decode(encoded).ok_or(Error::InvalidDidKey)?;
+        let (crv, public) = DID_KEY_CODECS
+            .iter()
+            .find_map(|(crv, codec)| Some((*crv, bytes.strip_prefix(*codec)?)))
+            .ok_or(Error::InvalidDidKey)?;
+        match crv {
+            "P-256" => {
+                use p256::arithmetic::AffinePoint;
+
+                let point = p256::PublicKey::from_bytes(public)
+                    .and_then(|pk| Option::<AffinePoint>::from(AffinePoint::from_pubkey(&pk)))
+                    .ok_or(Error::InvalidDidKey)?
+                    .to_uncompressed_pubkey();
+                let (x, y) = point.as_bytes()[1..].split_at(32);
+                let curve = Curve::from_components(crv, None, x, y).unwrap();
+                Ok(Self::EC { curve })
+            }
+            crv => {
+                let curve = OkpCurve::from_components(crv, None, public)
+                    .map_err(|_| Error::InvalidDidKey)?;
+                Ok(Self::OKP { curve })
+            }
+        }
+    }
+
+    /// Returns true iff both keys have the same public part (or, for symmetric keys, the same
+    /// value), as determined by their thumbprints. Unlike `==`, this ignores private components.
+    pub fn public_eq(&self, other: &Key) -> bool {
+        self.thumbprint() == other.thumbprint()
+    }
+
+    /// Like `thumbprint`, but using the provided hash function.
+    pub fn thumbprint_with(&self, hash: HashAlg) -> String {
+        use sha2::Digest;
+
+        use crate::utils::base64_encode_unpadded as b64;
+
+        // The required members, in lexicographic order and without whitespace (RFC 7638 §3.2).
+        let canonical = zeroize::Zeroizing::new(match self {
+            Self::EC { curve } => {
+                let (x, y) = match (curve, curve.components()) {
+                    (_, Some((_d, x, y))) => (b64(x), b64(y)),
+                    (Curve::Other { params, .. }, None) => {
+                        let member = |name| match params.get(name) {
+                            Some(serde_json::Value::String(value)) => value.clone(),
+                            _ => String::new(),
+                        };
+                        (member("x"), member("y"))
+                    }
+                    (_, None) => unreachable!("only unknown curves lack components"),
+                };
+                format!(
+                    r#"{{"crv":"{}","kty":"EC","x":"{}","y":"{}"}}"#,
+                    curve.name(),
+                    x,
+                    y
+                )
+            }
+            Self::RSA { public, .. } => format!(
+                r#"{{"e":"{}","kty":"RSA","n":"{}"}}"#,
+                PUBLIC_EXPONENT_B64,
+                b64(public.n.as_slice())
+            ),
+            Self::Symmetric { key } => format!(r#"{{"k":"{}","kty":"oct"}}"#, b64(key.as_slice())),
+            Self::OKP { curve } => format!(
+                r#"{{"crv":"{}","kty":"OKP","x":"{}"}}"#,
+                curve.name(),
+                b64(curve.components().1)
+            ),
+            Self::Unknown { kty, members } => {
+                let mut members = members.clone();
+                members.insert("kty".into(), kty.clone().into());
+                // `serde_json::Map` keeps its keys sorted.
+                serde_json::to_string(&members).unwrap()
+            }
+        });
+        let canonical = canonical.as_bytes();
+        match hash {
+            HashAlg::Sha1 => b64(sha1::Sha1::digest(canonical)),
+            HashAlg::Sha256 => b64(sha2::Sha256::digest(canonical)),
+            HashAlg::Sha384 => b64(sha2::Sha384::digest(canonical)),
+            HashAlg::Sha512 => b64(sha2::Sha512::digest(canonical)),
+        }
+    }
+
+    /// Returns true iff this key is at least as long as required for use with `alg`: HMAC keys
+    /// must be at least as long as the hash output
+    /// ([RFC 7518 §3.2](https://tools.ietf.org/html/rfc7518#section-3.2)) and RSA keys must be
+    /// at least 2048 bits long ([RFC 7518 §3.3](https://tools.ietf.org/html/rfc7518#section-3.3)).
+    /// Algorithms without such a requirement are always satisfied.
+    pub fn is_long_enough_for(&self, alg: &Algorithm) -> bool {
+        let min_bits = match alg {
+            Algorithm::HS256 => 256,
+            Algorithm::HS384 => 384,
+            Algorithm::HS512 => 512,
+            alg if matches!(alg.family(), AlgorithmFamily::Rsa | AlgorithmFamily::RsaPss) => 2048,
+            _ => return true,
+        };
+        self.num_bits() >= min_bits
+    }
+
+    /// Returns true iff this is a symmetric key having the length required of a content
+    /// encryption key for the named JWE `enc` algorithm
+    /// ([RFC 7518 §5.1](https://tools.ietf.org/html/rfc7518#section-5.1)).
+    /// The AES-CBC-HMAC algorithms use a combined MAC and encryption key of twice the AES key size.
+    pub fn is_valid_cek_for(&self, enc: &str) -> bool {
+        let key = match self {
+            Self::Symmetric { key } => key,
+            _ => return false,
+        };
+        let enc: Option<Algorithm> = serde_json::from_value(enc.into()).ok();
+        enc.as_ref().and_then(Algorithm::content_key_len) == Some(key.len())
+    }
+
     /// If this key is asymmetric, encodes it as PKCS#8.
+    ///
+    /// Intermediate buffers holding private material are zeroized, but wiping the returned
+    /// DER (e.g., by wrapping it in `zeroize::Zeroizing`) is the caller's responsibility.
     #[cfg(feature = "pkcs-convert")]
     pub fn try_to_der(&self) -> Result<Vec<u8>, ConversionError> {
-        use num_bigint::BigUint;
-        use yasna::{models::ObjectIdentifier, DERWriter, DERWriterSeq, Tag};
+        use yasna::models::ObjectIdentifier;
+
+        use crate::utils::pkcs8;
+
+        match self {
+            Self::Symmetric { .. } => return Err(ConversionError::NotAsymmetric),
+            Self::Unknown { .. } => return Err(ConversionError::UnsupportedKey),
+            _ => {}
+        }
+
+        Ok(match self {
+            Self::EC { curve } => {
+                let (d, x, y) = curve.components().ok_or(ConversionError::UnsupportedKey)?;
+                let ec_public_oid = ObjectIdentifier::from_slice(EC_PUBLIC_KEY_OID);
+                let curve_oid = ObjectIdentifier::from_slice(
+                    curve.oid().ok_or(ConversionError::UnsupportedKey)?,
+                );
+                let oids = &[Some(&ec_public_oid), Some(&curve_oid)];
+                match d {
+                    // The curve is given by the `PrivateKeyInfo`. OpenSSL repeats it in the
+                    // `ECPrivateKey`, but many tools, including jwt.io and `jsonwebtoken`,
+                    // don't like it, so we don't include it.
+                    Some(d) => pkcs8::write_private_bytes(oids, &write_sec1(d, x, y, None), None),
+                    None => pkcs8::write_public(oids, |writer| {
+                        let point = [&[0x04 /* uncompressed */][..], x, y].concat();
+                        writer.write_bitvec_bytes(&point, 8 * point.len());
+                    }),
+                }
+            }
+            Self::RSA { public, private } => {
+                let rsa_encryption_oid = ObjectIdentifier::from_slice(RSA_ENCRYPTION_OID);
+                let oids = &[Some(&rsa_encryption_oid), None];
+                let body = write_pkcs1(public, private.as_ref())?;
+                match private {
+                    Some(_) => pkcs8::write_private_bytes(oids, &body, None),
+                    None => pkcs8::write_public(oids, |writer| {
+                        writer.write_bitvec_bytes(&body, body.len() * 8)
+                    }),
+                }
+            }
+            Self::OKP { curve } => {
+                // https://tools.ietf.org/html/rfc8410#section-7
+                let curve_oid = ObjectIdentifier::from_slice(curve.oid());
+                let oids = &[Some(&curve_oid)];
+                match curve.components() {
+                    // Written as a v2 `OneAsymmetricKey` so that the public key is retained.
+                    (Some(d), x) => {
+                        let body = zeroize::Zeroizing::new(yasna::construct_der(|writer| {
+                            writer.write_bytes(d)
+                        }));
+                        pkcs8::write_private_bytes(oids, &body, Some(x))
+                    }
+                    (None, x) => pkcs8::write_public(oids, |writer| {
+                        writer.write_bitvec_bytes(x, x.len() * 8)
+                    }),
+                }
+            }
+            Self::Symmetric { .. } | Self::Unknown { .. } => unreachable!("checked above"),
+        })
+    }
+
+    /// Decodes a PKCS#8-encoded private key or a SubjectPublicKeyInfo-encoded public key.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn from_der(der: &[u8]) -> Result<Self, ConversionError> {
+        match utils::pkcs8::read_private(der) {
+            Ok(private_key_info) => Self::from_private_key_info(private_key_info),
+            Err(_) => Self::from_spki_der(der),
+        }
+    }
+
+    /// Decodes a SubjectPublicKeyInfo-encoded public key, such as those found in X.509
+    /// certificates or exported by TLS libraries.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn from_spki_der(der: &[u8]) -> Result<Self, ConversionError> {
+        use yasna::models::ObjectIdentifier;
+
+        let ((algorithm, params), body) = utils::pkcs8::read_public(der)?;
+        if algorithm == ObjectIdentifier::from_slice(EC_PUBLIC_KEY_OID) {
+            let crv = Curve::name_for_params(params.as_ref())?;
+            Ok(Self::EC {
+                curve: Curve::from_point(crv, None, &body)?,
+            })
+        } else if algorithm == ObjectIdentifier::from_slice(RSA_ENCRYPTION_OID) {
+            Self::read_pkcs1_public(&body)
+        } else if let Some(crv) = OkpCurve::name_for_oid(algorithm.components()) {
+            Ok(Self::OKP {
+                curve: OkpCurve::from_components(crv, None, &body).map_err(|_| invalid_der())?,
+            })
+        } else {
+            Err(ConversionError::UnsupportedKey)
+        }
+    }
+
+    /// Decodes a PKCS#8-encoded private key, as produced by `try_to_der`.
+    /// If an OKP key omits its public key (i.e., is PKCS#8 v1), the public key is derived from
+    /// the private key, which requires the `generate` or `crypto` feature (and, for Ed448, the
+    /// `generate` feature). X448 keys must always include their public key.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn from_pkcs8_der(der: &[u8]) -> Result<Self, ConversionError> {
+        Self::from_private_key_info(utils::pkcs8::read_private(der)?)
+    }
+
+    #[cfg(feature = "pkcs-convert")]
+    fn from_private_key_info(
+        private_key_info: utils::pkcs8::PrivateKeyInfo,
+    ) -> Result<Self, ConversionError> {
+        use yasna::models::ObjectIdentifier;
+        use zeroize::Zeroizing;
+
+        let utils::pkcs8::PrivateKeyInfo {
+            oids: (algorithm, params),
+            body,
+            public_key,
+        } = private_key_info;
+        if algorithm == ObjectIdentifier::from_slice(EC_PUBLIC_KEY_OID) {
+            Self::read_sec1(&body, params.as_ref())
+        } else if algorithm == ObjectIdentifier::from_slice(RSA_ENCRYPTION_OID) {
+            Self::read_pkcs1_private(&body)
+        } else if let Some(crv) = OkpCurve::name_for_oid(algorithm.components()) {
+            let d = Zeroizing::new(yasna::parse_der(&body, |reader| reader.read_bytes())?);
+            let public_key = match public_key {
+                Some(public_key) => public_key,
+                None => okp_public_key(crv, &d).ok_or(ConversionError::UnsupportedKey)?,
+            };
+            Ok(Self::OKP {
+                curve: OkpCurve::from_components(crv, Some(&d), &public_key)
+                    .map_err(|_| invalid_der())?,
+            })
+        } else {
+            Err(ConversionError::UnsupportedKey)
+        }
+    }
+
+    /// Decodes a PKCS#1 `RSAPrivateKey` (including all CRT parameters) or `RSAPublicKey`,
+    /// i.e., the contents of an `RSA PRIVATE KEY` or `RSA PUBLIC KEY` PEM block.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn from_pkcs1_der(der: &[u8]) -> Result<Self, ConversionError> {
+        Self::read_pkcs1_private(der).or_else(|_| Self::read_pkcs1_public(der))
+    }
+
+    /// Decodes a SEC1 `ECPrivateKey`, i.e., the contents of an `EC PRIVATE KEY` PEM block.
+    /// The curve is taken from the key's parameters field, or from `crv` if the key omits it.
+    /// If the key omits its public point, the point is computed from the private key, which is
+    /// only supported for P-256 (with the `generate`, `crypto`, or `did-key` feature) and
+    /// secp256k1 (with the `generate` feature).
+    #[cfg(feature = "pkcs-convert")]
+    pub fn from_sec1_der(der: &[u8], crv: Option<&str>) -> Result<Self, ConversionError> {
+        let params = match crv {
+            Some(crv) => Some(yasna::models::ObjectIdentifier::from_slice(
+                EC_CURVE_OIDS
+                    .iter()
+                    .find(|(name, _)| *name == crv)
+                    .ok_or(ConversionError::UnsupportedKey)?
+                    .1,
+            )),
+            None => None,
+        };
+        Self::read_sec1(der, params.as_ref())
+    }
+
+    /// Like `from_sec1_der`, but for the first `EC PRIVATE KEY` block of `pem`.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn from_sec1_pem(pem: &str, crv: Option<&str>) -> Result<Self, ConversionError> {
+        let (_label, der) = utils::pem::decode_blocks(pem)?
+            .into_iter()
+            .find(|(label, _der)| label == "EC PRIVATE KEY")
+            .ok_or(ConversionError::InvalidPem)?;
+        Self::from_sec1_der(&der, crv)
+    }
+
+    /// Decrypts and decodes a password-protected PKCS#8 `EncryptedPrivateKeyInfo`, such as those
+    /// written by `openssl pkcs8 -topk8`. Only PBES2 with PBKDF2 (using HMAC-SHA1 or HMAC-SHA2)
+    /// and AES-CBC is supported, with at most `jwe::PBES2_MAX_ITERATIONS` iterations so that
+    /// untrusted keys can't make this hang.
+    #[cfg(feature = "crypto")]
+    pub fn from_encrypted_pkcs8_der(
+        der: &[u8],
+        password: impl AsRef<[u8]>,
+    ) -> Result<Self, ConversionError> {
+        Self::from_pkcs8_der(&utils::pbes2::decrypt(der, password.as_ref())?)
+    }
+
+    /// Like `from_encrypted_pkcs8_der`, but for the first `ENCRYPTED PRIVATE KEY` block of `pem`.
+    #[cfg(feature = "crypto")]
+    pub fn from_encrypted_pkcs8_pem(
+        pem: &str,
+        password: impl AsRef<[u8]>,
+    ) -> Result<Self, ConversionError> {
+        let (_label, der) = utils::pem::decode_blocks(pem)?
+            .into_iter()
+            .find(|(label, _der)| label == "ENCRYPTED PRIVATE KEY")
+            .ok_or(ConversionError::InvalidPem)?;
+        Self::from_encrypted_pkcs8_der(&der, password)
+    }
+
+    /// Decodes the first key in `pem`, which may be a PKCS#8 `PRIVATE KEY`, a SubjectPublicKeyInfo
+    /// `PUBLIC KEY`, a PKCS#1 `RSA PRIVATE KEY` or `RSA PUBLIC KEY`, or a SEC1 `EC PRIVATE KEY`.
+    /// Other blocks, such as the `EC PARAMETERS` emitted by `openssl ecparam`, are skipped.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn from_pem(pem: &str) -> Result<Self, ConversionError> {
+        utils::pem::decode_blocks(pem)?
+            .iter()
+            .find_map(|(label, der)| Self::from_pem_block(label, der))
+            .unwrap_or(Err(ConversionError::InvalidPem))
+    }
+
+    /// Decodes the contents of a PEM block as `from_pem` does, returning `None` if the block
+    /// does not contain a key.
+    #[cfg(feature = "pkcs-convert")]
+    pub(crate) fn from_pem_block(label: &str, der: &[u8]) -> Option<Result<Self, ConversionError>> {
+        Some(match label {
+            "PRIVATE KEY" => Self::from_pkcs8_der(der),
+            "PUBLIC KEY" => Self::from_spki_der(der),
+            "RSA PRIVATE KEY" => Self::read_pkcs1_private(der),
+            "RSA PUBLIC KEY" => Self::read_pkcs1_public(der),
+            "EC PRIVATE KEY" => Self::read_sec1(der, None),
+            _ => return None,
+        })
+    }
+
+    /// Decodes a SEC1 `ECPrivateKey`, whose curve is given by `params` or, failing that,
+    /// by the key's own parameters field.
+    #[cfg(feature = "pkcs-convert")]
+    fn read_sec1(
+        der: &[u8],
+        params: Option<&yasna::models::ObjectIdentifier>,
+    ) -> Result<Self, ConversionError> {
+        use yasna::Tag;
+        use zeroize::Zeroizing;
+
+        let (d, curve_oid, public) = yasna::parse_der(der, |reader| {
+            reader.read_sequence(|reader| {
+                reader.next().read_u8()?; // version
+                let d = Zeroizing::new(reader.next().read_bytes()?);
+                let curve_oid = reader.read_optional(|reader| {
+                    reader.read_tagged(Tag::context(0), |reader| reader.read_oid())
+                })?;
+                let public = reader.read_optional(|reader| {
+                    reader.read_tagged(Tag::context(1), |reader| reader.read_bitvec_bytes())
+                })?;
+                Ok((d, curve_oid, public))
+            })
+        })?;
+        let crv = match (params, curve_oid.as_ref()) {
+            (Some(params), Some(curve_oid)) if params != curve_oid => {
+                return Err(ConversionError::UnsupportedKey)
+            }
+            (Some(oid), _) | (None, Some(oid)) => Curve::name_for_params(Some(oid))?,
+            (None, None) => return Err(ConversionError::UnsupportedKey),
+        };
+        let public = match public {
+            Some((public, _num_bits)) => public,
+            None => ec_public_point(crv, &d).ok_or(ConversionError::UnsupportedKey)?,
+        };
+        Ok(Self::EC {
+            curve: Curve::from_point(crv, Some(&d), &public)?,
+        })
+    }
+
+    /// Decodes a PKCS#1 `RSAPrivateKey`.
+    #[cfg(feature = "pkcs-convert")]
+    fn read_pkcs1_private(der: &[u8]) -> Result<Self, ConversionError> {
+        let (e_ok, public, private) = yasna::parse_der(der, |reader| {
+            reader.read_sequence(|reader| {
+                reader.next().read_u8()?; // version
+                let n = read_rsa_bytevec(reader)?;
+                let e_ok = read_rsa_public_exponent(reader)?;
+                let private = RsaPrivate {
+                    d: read_rsa_bytevec(reader)?,
+                    p: Some(read_rsa_bytevec(reader)?),
+                    q: Some(read_rsa_bytevec(reader)?),
+                    dp: Some(read_rsa_bytevec(reader)?),
+                    dq: Some(read_rsa_bytevec(reader)?),
+                    qi: Some(read_rsa_bytevec(reader)?),
+                };
+                let public = RsaPublic {
+                    e: PublicExponent,
+                    n,
+                };
+                Ok((e_ok, public, private))
+            })
+        })?;
+        if !e_ok {
+            return Err(ConversionError::UnsupportedKey);
+        }
+        Ok(Self::RSA {
+            public,
+            private: Some(private),
+        })
+    }
+
+    /// Decodes a PKCS#1 `RSAPublicKey`.
+    #[cfg(feature = "pkcs-convert")]
+    fn read_pkcs1_public(der: &[u8]) -> Result<Self, ConversionError> {
+        let (e_ok, n) = yasna::parse_der(der, |reader| {
+            reader.read_sequence(|reader| {
+                let n = read_rsa_bytevec(reader)?;
+                Ok((read_rsa_public_exponent(reader)?, n))
+            })
+        })?;
+        if !e_ok {
+            return Err(ConversionError::UnsupportedKey);
+        }
+        Ok(Self::RSA {
+            public: RsaPublic {
+                e: PublicExponent,
+                n,
+            },
+            private: None,
+        })
+    }
+
+    /// Unwrapping `try_to_der`.
+    /// Panics if the key is not asymmetric or there are missing RSA components.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn to_der(&self) -> Vec<u8> {
+        self.try_to_der().unwrap()
+    }
+
+    /// If this key is asymmetric, encodes its public part as a SubjectPublicKeyInfo, whether or
+    /// not this key is private.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn try_to_public_der(&self) -> Result<Vec<u8>, ConversionError> {
+        match self.to_public() {
+            Some(public) => public.try_to_der(),
+            None if matches!(self, Self::Symmetric { .. }) => Err(ConversionError::NotAsymmetric),
+            None => Err(ConversionError::UnsupportedKey),
+        }
+    }
+
+    /// Like `try_to_public_der`, but with `PUBLIC KEY` PEM armoring.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn try_to_public_pem(&self) -> Result<String, ConversionError> {
+        Ok(utils::pem::encode("PUBLIC KEY", &self.try_to_public_der()?))
+    }
+
+    /// If this key is asymmetric, encodes it as PKCS#8 with PEM armoring.
+    ///
+    /// As with `try_to_der`, wiping the returned PEM is the caller's responsibility.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn try_to_pem(&self) -> Result<String, ConversionError> {
+        let der = zeroize::Zeroizing::new(self.try_to_der()?);
+        let label = if self.is_private() {
+            "PRIVATE KEY"
+        } else {
+            "PUBLIC KEY"
+        };
+        Ok(utils::pem::encode(label, &der))
+    }
+
+    /// Like `try_to_pem`, but framed as described by `options`.
+    /// With `PemLabel::TypeSpecific`, RSA keys are encoded as PKCS#1 (`RSA PRIVATE KEY` or
+    /// `RSA PUBLIC KEY`) and private EC keys as SEC1 (`EC PRIVATE KEY`); other keys, which have
+    /// no type-specific encoding, are encoded as PKCS#8.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn try_to_pem_with(&self, options: &PemOptions) -> Result<String, ConversionError> {
+        let (label, der) = match (options.label, self) {
+            (PemLabel::TypeSpecific, Self::RSA { private, .. }) => (
+                if private.is_some() {
+                    "RSA PRIVATE KEY"
+                } else {
+                    "RSA PUBLIC KEY"
+                },
+                self.try_to_pkcs1_der()?,
+            ),
+            (PemLabel::TypeSpecific, Self::EC { .. }) if self.is_private() => {
+                ("EC PRIVATE KEY", self.try_to_sec1_der()?)
+            }
+            _ => (
+                if self.is_private() {
+                    "PRIVATE KEY"
+                } else {
+                    "PUBLIC KEY"
+                },
+                self.try_to_der()?,
+            ),
+        };
+        let der = zeroize::Zeroizing::new(der);
+        let eol = match options.line_ending {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        };
+        Ok(utils::pem::encode_with(
+            label,
+            &der,
+            options.line_width,
+            eol,
+        ))
+    }
+
+    /// Unwrapping `try_to_pem`.
+    /// Panics if the key is not asymmetric or there are missing RSA components.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn to_pem(&self) -> String {
+        self.try_to_pem().unwrap()
+    }
+
+    /// If this is an RSA key, encodes it as a PKCS#1 `RSAPrivateKey` or `RSAPublicKey`.
+    /// As with `try_to_der`, wiping the returned DER is the caller's responsibility.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn try_to_pkcs1_der(&self) -> Result<Vec<u8>, ConversionError> {
+        match self {
+            Self::RSA { public, private } => Ok(write_pkcs1(public, private.as_ref())?.to_vec()),
+            Self::Symmetric { .. } => Err(ConversionError::NotAsymmetric),
+            _ => Err(ConversionError::UnsupportedKey),
+        }
+    }
+
+    /// Like `try_to_pkcs1_der`, but with `RSA PRIVATE KEY` or `RSA PUBLIC KEY` PEM armoring.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn try_to_pkcs1_pem(&self) -> Result<String, ConversionError> {
+        let der = zeroize::Zeroizing::new(self.try_to_pkcs1_der()?);
+        let label = if self.is_private() {
+            "RSA PRIVATE KEY"
+        } else {
+            "RSA PUBLIC KEY"
+        };
+        Ok(utils::pem::encode(label, &der))
+    }
+
+    /// If this is a private EC key, encodes it as a SEC1 `ECPrivateKey` having the curve OID in
+    /// its parameters field. As with `try_to_der`, wiping the returned DER is the caller's
+    /// responsibility.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn try_to_sec1_der(&self) -> Result<Vec<u8>, ConversionError> {
+        let curve = match self {
+            Self::EC { curve } => curve,
+            Self::Symmetric { .. } => return Err(ConversionError::NotAsymmetric),
+            _ => return Err(ConversionError::UnsupportedKey),
+        };
+        let (d, x, y) = curve.components().ok_or(ConversionError::UnsupportedKey)?;
+        let d = d.ok_or(ConversionError::NotPrivate)?;
+        let curve_oid = yasna::models::ObjectIdentifier::from_slice(
+            curve.oid().ok_or(ConversionError::UnsupportedKey)?,
+        );
+        Ok(write_sec1(d, x, y, Some(&curve_oid)).to_vec())
+    }
+
+    /// Like `try_to_sec1_der`, but with `EC PRIVATE KEY` PEM armoring.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn try_to_sec1_pem(&self) -> Result<String, ConversionError> {
+        let der = zeroize::Zeroizing::new(self.try_to_sec1_der()?);
+        Ok(utils::pem::encode("EC PRIVATE KEY", &der))
+    }
+
+    /// If this is a private asymmetric key, encodes it as a PKCS#8 `EncryptedPrivateKeyInfo`,
+    /// protected by `password` using PBES2 as described by `params`.
+    #[cfg(feature = "crypto")]
+    pub fn try_to_encrypted_pkcs8_der(
+        &self,
+        password: impl AsRef<[u8]>,
+        params: &Pbes2Params,
+    ) -> Result<Vec<u8>, ConversionError> {
+        if !self.is_private() {
+            return Err(ConversionError::NotPrivate);
+        }
+        let der = zeroize::Zeroizing::new(self.try_to_der()?);
+        utils::pbes2::encrypt(&der, password.as_ref(), params)
+    }
+
+    /// Like `try_to_encrypted_pkcs8_der`, but with `ENCRYPTED PRIVATE KEY` PEM armoring, as
+    /// read by `openssl pkey` and `from_encrypted_pkcs8_pem`.
+    #[cfg(feature = "crypto")]
+    pub fn try_to_encrypted_pkcs8_pem(
+        &self,
+        password: impl AsRef<[u8]>,
+        params: &Pbes2Params,
+    ) -> Result<String, ConversionError> {
+        let der = self.try_to_encrypted_pkcs8_der(password, params)?;
+        Ok(utils::pem::encode("ENCRYPTED PRIVATE KEY", &der))
+    }
+
+    /// Returns the public key contained in a DER-encoded X.509 certificate.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn from_certificate_der(der: &[u8]) -> Result<Self, ConversionError> {
+        Self::from_spki_der(&utils::x509::read_public_key_info(der)?)
+    }
+
+    /// Returns the public key contained in the first `CERTIFICATE` block of `pem`.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn from_certificate_pem(pem: &str) -> Result<Self, ConversionError> {
+        let chain = utils::pem::decode_certificates(pem)?;
+        Self::from_certificate_der(chain.first().ok_or(ConversionError::InvalidPem)?)
+    }
+
+    /// Generates a new symmetric key with the specified number of bits.
+    /// Best used with one of the HS algorithms (e.g., HS256).
+    /// Panics if `num_bits` is not a non-zero multiple of 8;
+    /// see `try_generate_symmetric` for a checked alternative.
+    #[cfg(feature = "generate")]
+    pub fn generate_symmetric(num_bits: usize) -> Self {
+        Self::generate_symmetric_with_rng(num_bits, &mut rand::thread_rng())
+    }
+
+    /// Like `generate_symmetric`, but drawing randomness from `rng` (e.g., a DRBG, or a seeded RNG
+    /// in tests) instead of the thread-local RNG.
+    #[cfg(feature = "generate")]
+    pub fn generate_symmetric_with_rng(
+        num_bits: usize,
+        rng: &mut (impl rand::CryptoRng + rand::RngCore),
+    ) -> Self {
+        Self::try_generate_symmetric_with_rng(num_bits, rng).unwrap()
+    }
+
+    /// Generates a new symmetric key with the specified number of bits,
+    /// which must be a non-zero multiple of 8.
+    #[cfg(feature = "generate")]
+    pub fn try_generate_symmetric(num_bits: usize) -> Result<Self, Error> {
+        Self::try_generate_symmetric_with_rng(num_bits, &mut rand::thread_rng())
+    }
+
+    /// Like `try_generate_symmetric`, but drawing randomness from `rng`.
+    #[cfg(feature = "generate")]
+    pub fn try_generate_symmetric_with_rng(
+        num_bits: usize,
+        rng: &mut (impl rand::CryptoRng + rand::RngCore),
+    ) -> Result<Self, Error> {
+        if num_bits == 0 || !num_bits.is_multiple_of(8) {
+            return Err(Error::InvalidKeySize(num_bits));
+        }
+        let mut bytes = vec![0; num_bits / 8];
+        rng.fill_bytes(&mut bytes);
+        Ok(Self::Symmetric { key: bytes.into() })
+    }
+
+    /// Generates a new symmetric key of the size required or recommended for `alg`: the hash
+    /// output size for the HS algorithms, and the AES key size (doubled for the AES-CBC-HMAC
+    /// algorithms) for the key wrapping and content encryption algorithms.
+    /// Returns `Error::MismatchedAlgorithm` if `alg` doesn't use a symmetric key of fixed size.
+    #[cfg(feature = "generate")]
+    pub fn generate_symmetric_for(alg: &Algorithm) -> Result<Self, Error> {
+        Self::generate_symmetric_for_with_rng(alg, &mut rand::thread_rng())
+    }
+
+    /// Like `generate_symmetric_for`, but drawing randomness from `rng`.
+    #[cfg(feature = "generate")]
+    pub fn generate_symmetric_for_with_rng(
+        alg: &Algorithm,
+        rng: &mut (impl rand::CryptoRng + rand::RngCore),
+    ) -> Result<Self, Error> {
+        let len = alg.symmetric_key_len().ok_or(Error::MismatchedAlgorithm)?;
+        Self::try_generate_symmetric_with_rng(8 * len, rng)
+    }
+
+    /// Generates a new EC keypair using the prime256 curve.
+    /// Used with the ES256 algorithm.
+    #[cfg(feature = "generate")]
+    pub fn generate_p256() -> Self {
+        Self::generate_p256_with_rng(&mut rand::thread_rng())
+    }
+
+    /// Like `generate_p256`, but drawing randomness from `rng`.
+    #[cfg(feature = "generate")]
+    pub fn generate_p256_with_rng(rng: &mut (impl rand::CryptoRng + rand::RngCore)) -> Self {
+        use p256::elliptic_curve::generic_array::GenericArray;
+
+        let mut sk_bytes = GenericArray::default();
+        rng.fill_bytes(&mut sk_bytes);
+        let sk = p256::SecretKey::new(sk_bytes);
+        let sk_scalar = p256::arithmetic::Scalar::from_secret(sk).unwrap();
+
+        let pk = p256::arithmetic::ProjectivePoint::generator() * &sk_scalar;
+        let pk_bytes = &pk
+            .to_affine()
+            .unwrap()
+            .to_uncompressed_pubkey()
+            .into_bytes()[1..];
+        let (x_bytes, y_bytes) = pk_bytes.split_at(32);
+
+        Self::EC {
+            curve: Curve::P256 {
+                d: Some(ByteArray::try_from_slice(sk_scalar.to_bytes()).unwrap()),
+                x: ByteArray::try_from_slice(x_bytes).unwrap(),
+                y: ByteArray::try_from_slice(y_bytes).unwrap(),
+            },
+        }
+    }
+
+    /// Generates a new EC keypair using the secp384r1 curve.
+    /// Used with the ES384 algorithm. Requires the `crypto` feature, as generation uses `ring`,
+    /// which is also why there is no `_with_rng` variant: `ring` only uses the system RNG.
+    #[cfg(all(feature = "generate", feature = "crypto"))]
+    pub fn generate_p384() -> Self {
+        use ring::signature::{EcdsaKeyPair, ECDSA_P384_SHA384_FIXED_SIGNING};
+
+        let rng = ring::rand::SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P384_SHA384_FIXED_SIGNING, &rng).unwrap();
+        Self::from_pkcs8_der(pkcs8.as_ref()).unwrap()
+    }
+
+    /// Generates a new Ed25519 keypair.
+    /// Used with the EdDSA algorithm.
+    #[cfg(feature = "generate")]
+    pub fn generate_ed25519() -> Self {
+        Self::generate_ed25519_with_rng(&mut rand::thread_rng())
+    }
+
+    /// Like `generate_ed25519`, but drawing randomness from `rng`.
+    #[cfg(feature = "generate")]
+    pub fn generate_ed25519_with_rng(rng: &mut (impl rand::CryptoRng + rand::RngCore)) -> Self {
+        use zeroize::Zeroizing;
+
+        let mut d = Zeroizing::new([0u8; 32]);
+        rng.fill_bytes(&mut *d);
+        let x = utils::curve25519::ed25519_public(&d);
+
+        Self::OKP {
+            curve: OkpCurve::Ed25519 {
+                d: Some(ByteArray::try_from_slice(&d[..]).unwrap()),
+                x: ByteArray::try_from_slice(x).unwrap(),
+            },
+        }
+    }
+
+    /// Generates a new X25519 keypair.
+    /// Used with the ECDH-ES algorithms.
+    #[cfg(feature = "generate")]
+    pub fn generate_x25519() -> Self {
+        Self::generate_x25519_with_rng(&mut rand::thread_rng())
+    }
+
+    /// Like `generate_x25519`, but drawing randomness from `rng`.
+    #[cfg(feature = "generate")]
+    pub fn generate_x25519_with_rng(rng: &mut (impl rand::CryptoRng + rand::RngCore)) -> Self {
+        use zeroize::Zeroizing;
+
+        let mut d = Zeroizing::new([0u8; 32]);
+        rng.fill_bytes(&mut *d);
+        let x = utils::curve25519::x25519_public(&d);
+
+        Self::OKP {
+            curve: OkpCurve::X25519 {
+                d: Some(ByteArray::try_from_slice(&d[..]).unwrap()),
+                x: ByteArray::try_from_slice(x).unwrap(),
+            },
+        }
+    }
+
+    /// Generates a new Ed448 keypair.
+    /// Used with the EdDSA algorithm.
+    #[cfg(feature = "generate")]
+    pub fn generate_ed448() -> Self {
+        Self::generate_ed448_with_rng(&mut rand::thread_rng())
+    }
+
+    /// Like `generate_ed448`, but drawing randomness from `rng`.
+    #[cfg(feature = "generate")]
+    pub fn generate_ed448_with_rng(rng: &mut (impl rand::CryptoRng + rand::RngCore)) -> Self {
+        use zeroize::Zeroizing;
+
+        let mut d = Zeroizing::new([0u8; 57]);
+        rng.fill_bytes(&mut *d);
+        let private_key = ed448_rust::PrivateKey::from(&*d);
+        let x = ed448_rust::PublicKey::from(&private_key).as_byte();
+
+        Self::OKP {
+            curve: OkpCurve::Ed448 {
+                d: Some(ByteArray::try_from_slice(&d[..]).unwrap()),
+                x: ByteArray::try_from_slice(x).unwrap(),
+            },
+        }
+    }
+
+    /// Generates a new EC keypair using the secp256k1 curve.
+    /// Used with the ES256K algorithm.
+    #[cfg(feature = "generate")]
+    pub fn generate_secp256k1() -> Self {
+        Self::generate_secp256k1_with_rng(&mut rand::thread_rng())
+    }
+
+    /// Like `generate_secp256k1`, but drawing randomness from `rng`.
+    #[cfg(feature = "generate")]
+    pub fn generate_secp256k1_with_rng(rng: &mut (impl rand::CryptoRng + rand::RngCore)) -> Self {
+        use zeroize::Zeroize;
+
+        let sk_scalar = loop {
+            let mut sk_bytes = [0u8; 32];
+            rng.fill_bytes(&mut sk_bytes);
+            let sk_scalar = k256::arithmetic::Scalar::from_bytes(sk_bytes);
+            sk_bytes.zeroize();
+            if bool::from(sk_scalar.is_some()) {
+                break sk_scalar.unwrap();
+            }
+        };
+
+        let pk = k256::arithmetic::ProjectivePoint::generator() * &sk_scalar;
+        let pk_bytes = &pk
+            .to_affine()
+            .unwrap()
+            .to_uncompressed_pubkey()
+            .into_bytes()[1..];
+        let (x_bytes, y_bytes) = pk_bytes.split_at(32);
+
+        let mut sk_bytes = sk_scalar.to_bytes();
+        let d = ByteArray::try_from_slice(sk_bytes).unwrap();
+        sk_bytes.zeroize();
+
+        Self::EC {
+            curve: Curve::Secp256k1 {
+                d: Some(d),
+                x: ByteArray::try_from_slice(x_bytes).unwrap(),
+                y: ByteArray::try_from_slice(y_bytes).unwrap(),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "crv", remote = "Self")]
+pub enum Curve {
+    /// Parameters of the prime256v1 (P256) curve.
+    #[serde(rename = "P-256")]
+    P256 {
+        /// The private scalar. Scalars from which leading zeros have been stripped are accepted.
+        #[serde(
+            default,
+            deserialize_with = "byte_array::deserialize_opt_padded",
+            skip_serializing_if = "Option::is_none"
+        )]
+        d: Option<ByteArray<U32>>,
+        /// The curve point x coordinate.
+        x: ByteArray<U32>,
+        /// The curve point y coordinate.
+        y: ByteArray<U32>,
+    },
+    /// Parameters of the secp384r1 (P-384) curve.
+    #[serde(rename = "P-384")]
+    P384 {
+        /// The private scalar. Scalars from which leading zeros have been stripped are accepted.
+        #[serde(
+            default,
+            deserialize_with = "byte_array::deserialize_opt_padded",
+            skip_serializing_if = "Option::is_none"
+        )]
+        d: Option<ByteArray<U48>>,
+        /// The curve point x coordinate.
+        x: ByteArray<U48>,
+        /// The curve point y coordinate.
+        y: ByteArray<U48>,
+    },
+    /// Parameters of the secp521r1 (P-521) curve.
+    #[serde(rename = "P-521")]
+    P521 {
+        /// The private scalar. Scalars from which leading zeros have been stripped are accepted.
+        #[serde(
+            default,
+            deserialize_with = "byte_array::deserialize_opt_padded",
+            skip_serializing_if = "Option::is_none"
+        )]
+        d: Option<ByteArray<U66>>,
+        /// The curve point x coordinate.
+        x: ByteArray<U66>,
+        /// The curve point y coordinate.
+        y: ByteArray<U66>,
+    },
+    /// Parameters of the secp256k1 curve.
+    #[serde(rename = "secp256k1")]
+    Secp256k1 {
+        /// The private scalar. Scalars from which leading zeros have been stripped are accepted.
+        #[serde(
+            default,
+            deserialize_with = "byte_array::deserialize_opt_padded",
+            skip_serializing_if = "Option::is_none"
+        )]
+        d: Option<ByteArray<U32>>,
+        /// The curve point x coordinate.
+        x: ByteArray<U32>,
+        /// The curve point y coordinate.
+        y: ByteArray<U32>,
+    },
+    /// Parameters of the brainpoolP256r1 curve ([RFC 5639](https://tools.ietf.org/html/rfc5639)).
+    #[serde(rename = "brainpoolP256r1")]
+    BrainpoolP256r1 {
+        /// The private scalar. Scalars from which leading zeros have been stripped are accepted.
+        #[serde(
+            default,
+            deserialize_with = "byte_array::deserialize_opt_padded",
+            skip_serializing_if = "Option::is_none"
+        )]
+        d: Option<ByteArray<U32>>,
+        /// The curve point x coordinate.
+        x: ByteArray<U32>,
+        /// The curve point y coordinate.
+        y: ByteArray<U32>,
+    },
+    /// Parameters of the brainpoolP384r1 curve ([RFC 5639](https://tools.ietf.org/html/rfc5639)).
+    #[serde(rename = "brainpoolP384r1")]
+    BrainpoolP384r1 {
+        /// The private scalar. Scalars from which leading zeros have been stripped are accepted.
+        #[serde(
+            default,
+            deserialize_with = "byte_array::deserialize_opt_padded",
+            skip_serializing_if = "Option::is_none"
+        )]
+        d: Option<ByteArray<U48>>,
+        /// The curve point x coordinate.
+        x: ByteArray<U48>,
+        /// The curve point y coordinate.
+        y: ByteArray<U48>,
+    },
+    /// Parameters of the brainpoolP512r1 curve ([RFC 5639](https://tools.ietf.org/html/rfc5639)).
+    #[serde(rename = "brainpoolP512r1")]
+    BrainpoolP512r1 {
+        /// The private scalar. Scalars from which leading zeros have been stripped are accepted.
+        #[serde(
+            default,
+            deserialize_with = "byte_array::deserialize_opt_padded",
+            skip_serializing_if = "Option::is_none"
+        )]
+        d: Option<ByteArray<U64>>,
+        /// The curve point x coordinate.
+        x: ByteArray<U64>,
+        /// The curve point y coordinate.
+        y: ByteArray<U64>,
+    },
+    /// The parameters of a curve which is not supported by this crate, kept verbatim so that
+    /// the key can be round-tripped. Such keys are not valid for any algorithm.
+    #[serde(skip)]
+    Other {
+        /// The value of the `crv` member.
+        crv: String,
+        /// The remaining members (e.g., `x`, `y`, and `d`).
+        params: serde_json::Map<String, serde_json::Value>,
+    },
+}
+
+impl Serialize for Curve {
+    fn serialize<S: serde::ser::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+        match self {
+            Self::Other { crv, params } => {
+                let mut map = s.serialize_map(Some(params.len() + 1))?;
+                map.serialize_entry("crv", crv)?;
+                for (name, value) in params {
+                    map.serialize_entry(name, value)?;
+                }
+                map.end()
+            }
+            _ => Self::serialize(self, s),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Curve {
+    fn deserialize<D: serde::de::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        use serde::de::Error as _;
+        let mut params = serde_json::Map::deserialize(d)?;
+        let crv = match params.get("crv") {
+            Some(serde_json::Value::String(crv)) => crv.clone(),
+            Some(_) => return Err(D::Error::custom("`crv` must be a string")),
+            None => return Err(D::Error::missing_field("crv")),
+        };
+        if Self::is_supported(&crv) {
+            Self::deserialize(serde_json::Value::Object(params)).map_err(D::Error::custom)
+        } else {
+            params.remove("crv");
+            Ok(Self::Other { crv, params })
+        }
+    }
+}
+
+/// The `crv` names and namedCurve OIDs of the supported elliptic curves.
+#[cfg(feature = "pkcs-convert")]
+const EC_CURVE_OIDS: &[(&str, &[u64])] = &[
+    ("P-256", &[1, 2, 840, 10045, 3, 1, 7]), // prime256v1
+    ("P-384", &[1, 3, 132, 0, 34]),          // secp384r1
+    ("P-521", &[1, 3, 132, 0, 35]),          // secp521r1
+    ("secp256k1", &[1, 3, 132, 0, 10]),      // secp256k1
+    ("brainpoolP256r1", &[1, 3, 36, 3, 3, 2, 8, 1, 1, 7]),
+    ("brainpoolP384r1", &[1, 3, 36, 3, 3, 2, 8, 1, 1, 11]),
+    ("brainpoolP512r1", &[1, 3, 36, 3, 3, 2, 8, 1, 1, 13]),
+];
+
+#[cfg(feature = "pkcs-convert")]
+const EC_PUBLIC_KEY_OID: &[u64] = &[1, 2, 840, 10045, 2, 1];
+#[cfg(feature = "pkcs-convert")]
+const RSA_ENCRYPTION_OID: &[u64] = &[1, 2, 840, 113549, 1, 1, 1];
+
+#[cfg(feature = "pkcs-convert")]
+fn invalid_der() -> ConversionError {
+    use yasna::{ASN1Error, ASN1ErrorKind};
+    ConversionError::InvalidDer(ASN1Error::new(ASN1ErrorKind::Invalid))
+}
+
+#[cfg(feature = "pkcs-convert")]
+fn read_rsa_bytevec(reader: &mut yasna::BERReaderSeq) -> yasna::ASN1Result<ByteVec> {
+    Ok(ByteVec::from(reader.next().read_biguint()?.to_bytes_be()))
+}
+
+/// Computes the uncompressed SEC1 public point of the EC private key `d`, if the enabled
+/// features support `crv`.
+#[cfg(feature = "pkcs-convert")]
+fn ec_public_point(crv: &str, d: &[u8]) -> Option<Vec<u8>> {
+    use zeroize::Zeroizing;
+
+    let mut scalar_bytes = Zeroizing::new([0u8; 32]);
+    if d.len() > scalar_bytes.len() {
+        return None;
+    }
+    let offset = scalar_bytes.len() - d.len();
+    scalar_bytes[offset..].copy_from_slice(d);
+    match crv {
+        #[cfg(any(feature = "generate", feature = "crypto", feature = "did-key"))]
+        "P-256" => {
+            use p256::arithmetic::{AffinePoint, ProjectivePoint, Scalar};
+
+            let scalar = Option::<Scalar>::from(Scalar::from_bytes(*scalar_bytes))?;
+            let point =
+                Option::<AffinePoint>::from((ProjectivePoint::generator() * &scalar).to_affine())?;
+            Some(point.to_uncompressed_pubkey().as_bytes().to_vec())
+        }
+        #[cfg(feature = "generate")]
+        "secp256k1" => {
+            use k256::arithmetic::{AffinePoint, ProjectivePoint, Scalar};
+
+            let scalar = Option::<Scalar>::from(Scalar::from_bytes(*scalar_bytes))?;
+            let point =
+                Option::<AffinePoint>::from((ProjectivePoint::generator() * &scalar).to_affine())?;
+            Some(point.to_uncompressed_pubkey().as_bytes().to_vec())
+        }
+        _ => None,
+    }
+}
+
+/// Derives the public key of the OKP private key `d`, if the enabled features support `crv`.
+#[cfg(feature = "pkcs-convert")]
+#[cfg_attr(
+    not(any(feature = "generate", feature = "crypto")),
+    allow(unused_variables)
+)]
+fn okp_public_key(crv: &str, d: &[u8]) -> Option<Vec<u8>> {
+    match crv {
+        #[cfg(any(feature = "generate", feature = "crypto"))]
+        "Ed25519" => {
+            let seed = std::convert::TryFrom::try_from(d).ok()?;
+            Some(utils::curve25519::ed25519_public(seed).to_vec())
+        }
+        #[cfg(any(feature = "generate", feature = "crypto"))]
+        "X25519" => {
+            let scalar = std::convert::TryFrom::try_from(d).ok()?;
+            Some(utils::curve25519::x25519_public(scalar).to_vec())
+        }
+        #[cfg(feature = "generate")]
+        "Ed448" => {
+            let seed: &[u8; 57] = std::convert::TryFrom::try_from(d).ok()?;
+            let private_key = ed448_rust::PrivateKey::from(seed);
+            Some(ed448_rust::PublicKey::from(&private_key).as_byte().to_vec())
+        }
+        _ => None,
+    }
+}
+
+/// Encodes a SEC1 `ECPrivateKey`, with the namedCurve `params` if provided.
+#[cfg(feature = "pkcs-convert")]
+fn write_sec1(
+    d: &[u8],
+    x: &[u8],
+    y: &[u8],
+    params: Option<&yasna::models::ObjectIdentifier>,
+) -> zeroize::Zeroizing<Vec<u8>> {
+    use yasna::Tag;
+
+    zeroize::Zeroizing::new(yasna::construct_der(|writer| {
+        writer.write_sequence(|writer| {
+            // https://tools.ietf.org/html/rfc5915#section-3
+            writer.next().write_i8(1); // version
+            writer.next().write_bytes(d);
+            if let Some(params) = params {
+                writer
+                    .next()
+                    .write_tagged(Tag::context(0), |writer| writer.write_oid(params));
+            }
+            writer.next().write_tagged(Tag::context(1), |writer| {
+                let point = [&[0x04 /* uncompressed */][..], x, y].concat();
+                writer.write_bitvec_bytes(&point, 8 * point.len());
+            });
+        })
+    }))
+}
+
+/// Encodes a PKCS#1 `RSAPrivateKey` if `private` is provided, or an `RSAPublicKey` otherwise.
+#[cfg(feature = "pkcs-convert")]
+fn write_pkcs1(
+    public: &RsaPublic,
+    private: Option<&RsaPrivate>,
+) -> Result<zeroize::Zeroizing<Vec<u8>>, ConversionError> {
+    use num_bigint::BigUint;
+    use yasna::{DERWriter, DERWriterSeq};
 
-        use crate::utils::pkcs8;
+    let write_bytevec = |writer: DERWriter, vec: &ByteVec| {
+        let bigint = BigUint::from_bytes_be(vec.as_slice());
+        writer.write_biguint(&bigint);
+    };
+    let write_public = |writer: &mut DERWriterSeq| {
+        write_bytevec(writer.next(), &public.n);
+        writer.next().write_u32(PUBLIC_EXPONENT);
+    };
+
+    let body = match private {
+        Some(
+            private @ RsaPrivate {
+                d: _,
+                p: Some(_),
+                q: Some(_),
+                dp: Some(_),
+                dq: Some(_),
+                qi: Some(_),
+            },
+        ) => yasna::construct_der(|writer| {
+            writer.write_sequence(|writer| {
+                // https://tools.ietf.org/html/rfc3447#appendix-A.1.2
+                writer.next().write_i8(0); // version (two-prime)
+                write_public(writer);
+                write_bytevec(writer.next(), &private.d);
+                macro_rules! write_opt_bytevecs {
+                    ($($param:ident),+) => {{
+                        $(write_bytevec(writer.next(), private.$param.as_ref().unwrap());)+
+                    }};
+                }
+                write_opt_bytevecs!(p, q, dp, dq, qi);
+            })
+        }),
+        Some(_) => return Err(ConversionError::MissingRsaParams),
+        None => yasna::construct_der(|writer| writer.write_sequence(write_public)),
+    };
+    Ok(zeroize::Zeroizing::new(body))
+}
+
+/// Reads an RSA public exponent, returning whether it is the only supported one, 65537.
+#[cfg(feature = "pkcs-convert")]
+fn read_rsa_public_exponent(reader: &mut yasna::BERReaderSeq) -> yasna::ASN1Result<bool> {
+    Ok(reader.next().read_biguint()? == num_bigint::BigUint::from(PUBLIC_EXPONENT))
+}
 
-        if let Self::Symmetric { .. } = self {
-            return Err(ConversionError::NotAsymmetric);
+impl Curve {
+    /// Returns the name of this curve, as used in the `crv` member.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::P256 { .. } => "P-256",
+            Self::P384 { .. } => "P-384",
+            Self::P521 { .. } => "P-521",
+            Self::Secp256k1 { .. } => "secp256k1",
+            Self::BrainpoolP256r1 { .. } => "brainpoolP256r1",
+            Self::BrainpoolP384r1 { .. } => "brainpoolP384r1",
+            Self::BrainpoolP512r1 { .. } => "brainpoolP512r1",
+            Self::Other { crv, .. } => crv,
         }
+    }
 
-        Ok(match self {
-            Self::EC {
-                curve: Curve::P256 { d, x, y },
-            } => {
-                let ec_public_oid = ObjectIdentifier::from_slice(&[1, 2, 840, 10045, 2, 1]);
-                let prime256v1_oid = ObjectIdentifier::from_slice(&[1, 2, 840, 10045, 3, 1, 7]);
-                let oids = &[Some(&ec_public_oid), Some(&prime256v1_oid)];
-
-                let write_public = |writer: DERWriter| {
-                    let public_bytes: Vec<u8> = [0x04 /* uncompressed */]
-                        .iter()
-                        .chain(x.iter())
-                        .chain(y.iter())
-                        .copied()
-                        .collect();
-                    writer.write_bitvec_bytes(&public_bytes, 8 * (32 * 2 + 1));
-                };
+    /// Returns true iff the curve named `crv` is modeled by a variant other than `Other`.
+    fn is_supported(crv: &str) -> bool {
+        matches!(
+            crv,
+            "P-256"
+                | "P-384"
+                | "P-521"
+                | "secp256k1"
+                | "brainpoolP256r1"
+                | "brainpoolP384r1"
+                | "brainpoolP512r1"
+        )
+    }
 
-                match d {
-                    Some(private_point) => {
-                        pkcs8::write_private(oids, |writer: &mut DERWriterSeq| {
-                            writer.next().write_i8(1); // version
-                            writer.next().write_bytes(&**private_point);
-                            // The following tagged value is optional. OpenSSL produces it,
-                            // but many tools, including jwt.io and `jsonwebtoken`, don't like it,
-                            // so we don't include it.
-                            // writer.next().write_tagged(Tag::context(0), |writer| {
-                            //     writer.write_oid(&prime256v1_oid)
-                            // });
-                            writer.next().write_tagged(Tag::context(1), write_public);
-                        })
-                    }
-                    None => pkcs8::write_public(oids, write_public),
-                }
-            }
-            Self::RSA { public, private } => {
-                let rsa_encryption_oid = ObjectIdentifier::from_slice(&[
-                    1, 2, 840, 113549, 1, 1, 1, // rsaEncryption
-                ]);
-                let oids = &[Some(&rsa_encryption_oid), None];
-                let write_bytevec = |writer: DERWriter, vec: &ByteVec| {
-                    let bigint = BigUint::from_bytes_be(vec.as_slice());
-                    writer.write_biguint(&bigint);
-                };
+    /// Returns true iff these parameters include the private scalar.
+    fn is_private(&self) -> bool {
+        match self {
+            Self::Other { params, .. } => params.contains_key("d"),
+            _ => self.components().unwrap().0.is_some(),
+        }
+    }
 
-                let write_public = |writer: &mut DERWriterSeq| {
-                    write_bytevec(writer.next(), &public.n);
-                    writer.next().write_u32(PUBLIC_EXPONENT);
-                };
+    /// Returns the size of this curve's field elements in bits, or zero if the curve is unknown.
+    fn num_bits(&self) -> usize {
+        match self {
+            Self::P256 { .. } => 256,
+            Self::P384 { .. } => 384,
+            Self::P521 { .. } => 521,
+            Self::Secp256k1 { .. } => 256,
+            Self::BrainpoolP256r1 { .. } => 256,
+            Self::BrainpoolP384r1 { .. } => 384,
+            Self::BrainpoolP512r1 { .. } => 512,
+            Self::Other { .. } => 0,
+        }
+    }
 
-                let write_private = |writer: &mut DERWriterSeq, private: &RsaPrivate| {
-                    // https://tools.ietf.org/html/rfc3447#appendix-A.1.2
-                    writer.next().write_i8(0); // version (two-prime)
-                    write_public(writer);
-                    write_bytevec(writer.next(), &private.d);
-                    macro_rules! write_opt_bytevecs {
-                            ($($param:ident),+) => {{
-                                $(write_bytevec(writer.next(), private.$param.as_ref().unwrap());)+
-                            }};
-                        }
-                    write_opt_bytevecs!(p, q, dp, dq, qi);
-                };
+    /// Returns the private scalar, if any, and the public point's x and y coordinates,
+    /// or `None` if the curve is unknown.
+    #[allow(clippy::type_complexity)]
+    fn components(&self) -> Option<(Option<&[u8]>, &[u8], &[u8])> {
+        Some(match self {
+            Self::P256 { d, x, y } => (d.as_ref().map(|d| d.as_slice()), x, y),
+            Self::P384 { d, x, y } => (d.as_ref().map(|d| d.as_slice()), x, y),
+            Self::P521 { d, x, y } => (d.as_ref().map(|d| d.as_slice()), x, y),
+            Self::Secp256k1 { d, x, y } => (d.as_ref().map(|d| d.as_slice()), x, y),
+            Self::BrainpoolP256r1 { d, x, y } => (d.as_ref().map(|d| d.as_slice()), x, y),
+            Self::BrainpoolP384r1 { d, x, y } => (d.as_ref().map(|d| d.as_slice()), x, y),
+            Self::BrainpoolP512r1 { d, x, y } => (d.as_ref().map(|d| d.as_slice()), x, y),
+            Self::Other { .. } => return None,
+        })
+    }
 
-                match private {
-                    Some(
-                        private
-                        @
-                        RsaPrivate {
-                            d: _,
-                            p: Some(_),
-                            q: Some(_),
-                            dp: Some(_),
-                            dq: Some(_),
-                            qi: Some(_),
-                        },
-                    ) => pkcs8::write_private(oids, |writer| write_private(writer, private)),
-                    Some(_) => return Err(ConversionError::MissingRsaParams),
-                    None => pkcs8::write_public(oids, |writer| {
-                        let body =
-                            yasna::construct_der(|writer| writer.write_sequence(write_public));
-                        writer.write_bitvec_bytes(&body, body.len() * 8);
-                    }),
+    /// Constructs the parameters of the curve named `crv` from its components.
+    fn from_components(crv: &str, d: Option<&[u8]>, x: &[u8], y: &[u8]) -> Result<Self, String> {
+        macro_rules! curve {
+            ($variant:ident) => {
+                Self::$variant {
+                    d: d.map(ByteArray::try_from_slice_padded).transpose()?,
+                    x: ByteArray::try_from_slice(x)?,
+                    y: ByteArray::try_from_slice(y)?,
                 }
-            }
-            Self::Symmetric { .. } => unreachable!("checked above"),
+            };
+        }
+        Ok(match crv {
+            "P-256" => curve!(P256),
+            "P-384" => curve!(P384),
+            "P-521" => curve!(P521),
+            "secp256k1" => curve!(Secp256k1),
+            "brainpoolP256r1" => curve!(BrainpoolP256r1),
+            "brainpoolP384r1" => curve!(BrainpoolP384r1),
+            "brainpoolP512r1" => curve!(BrainpoolP512r1),
+            _ => return Err(format!("unsupported curve: {}", crv)),
         })
     }
 
-    /// Unwrapping `try_to_der`.
-    /// Panics if the key is not asymmetric or there are missing RSA components.
-    #[cfg(feature = "pkcs-convert")]
-    pub fn to_der(&self) -> Vec<u8> {
-        self.try_to_der().unwrap()
+    /// Returns these parameters without the private scalar.
+    fn to_public(&self) -> Self {
+        match self.components() {
+            Some((_d, x, y)) => Self::from_components(self.name(), None, x, y).unwrap(),
+            None => {
+                let mut public = self.clone();
+                if let Self::Other { params, .. } = &mut public {
+                    params.remove("d");
+                }
+                public
+            }
+        }
     }
 
-    /// If this key is asymmetric, encodes it as PKCS#8 with PEM armoring.
+    /// Returns the namedCurve OID of this curve, if known.
     #[cfg(feature = "pkcs-convert")]
-    pub fn try_to_pem(&self) -> Result<String, ConversionError> {
-        use std::fmt::Write;
-        let der_b64 = base64::encode(self.try_to_der()?);
-        let key_ty = if self.is_private() {
-            "PRIVATE"
-        } else {
-            "PUBLIC"
-        };
-        let mut pem = String::new();
-        writeln!(&mut pem, "-----BEGIN {} KEY-----", key_ty).unwrap();
-        //^ re: `unwrap`, if writing to a string fails, we've got bigger issues.
-        const MAX_LINE_LEN: usize = 64;
-        for i in (0..der_b64.len()).step_by(MAX_LINE_LEN) {
-            writeln!(
-                &mut pem,
-                "{}",
-                &der_b64[i..std::cmp::min(i + MAX_LINE_LEN, der_b64.len())]
-            )
-            .unwrap();
-        }
-        writeln!(&mut pem, "-----END {} KEY-----", key_ty).unwrap();
-        Ok(pem)
+    fn oid(&self) -> Option<&'static [u64]> {
+        let name = self.name();
+        EC_CURVE_OIDS
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, oid)| *oid)
     }
 
-    /// Unwrapping `try_to_pem`.
-    /// Panics if the key is not asymmetric or there are missing RSA components.
+    /// Returns the name of the curve having the provided namedCurve OID.
     #[cfg(feature = "pkcs-convert")]
-    pub fn to_pem(&self) -> String {
-        self.try_to_pem().unwrap()
+    fn name_for_oid(oid: &[u64]) -> Option<&'static str> {
+        EC_CURVE_OIDS
+            .iter()
+            .find(|(_, curve_oid)| *curve_oid == oid)
+            .map(|(name, _)| *name)
     }
 
-    /// Generates a new symmetric key with the specified number of bits.
-    /// Best used with one of the HS algorithms (e.g., HS256).
-    #[cfg(feature = "generate")]
-    pub fn generate_symmetric(num_bits: usize) -> Self {
-        use rand::RngCore;
-        let mut bytes = vec![0; num_bits / 8];
-        rand::thread_rng().fill_bytes(&mut bytes);
-        Self::Symmetric { key: bytes.into() }
+    /// Returns the name of the curve given by the namedCurve `params` of an EC key.
+    #[cfg(feature = "pkcs-convert")]
+    fn name_for_params(
+        params: Option<&yasna::models::ObjectIdentifier>,
+    ) -> Result<&'static str, ConversionError> {
+        params
+            .and_then(|oid| Self::name_for_oid(oid.components()))
+            .ok_or(ConversionError::UnsupportedKey)
     }
 
-    /// Generates a new EC keypair using the prime256 curve.
-    /// Used with the ES256 algorithm.
-    #[cfg(feature = "generate")]
-    pub fn generate_p256() -> Self {
-        use p256::elliptic_curve::generic_array::GenericArray;
-        use rand::RngCore;
-
-        let mut sk_bytes = GenericArray::default();
-        rand::thread_rng().fill_bytes(&mut sk_bytes);
-        let sk = p256::SecretKey::new(sk_bytes);
-        let sk_scalar = p256::arithmetic::Scalar::from_secret(sk).unwrap();
-
-        let pk = p256::arithmetic::ProjectivePoint::generator() * &sk_scalar;
-        let pk_bytes = &pk
-            .to_affine()
-            .unwrap()
-            .to_uncompressed_pubkey()
-            .into_bytes()[1..];
-        let (x_bytes, y_bytes) = pk_bytes.split_at(32);
-
-        Self::EC {
-            curve: Curve::P256 {
-                d: Some(sk_scalar.to_bytes().into()),
-                x: ByteArray::try_from_slice(x_bytes).unwrap(),
-                y: ByteArray::try_from_slice(y_bytes).unwrap(),
-            },
+    /// Makes a curve from its private key, if any, and its uncompressed SEC1 public point.
+    #[cfg(feature = "pkcs-convert")]
+    fn from_point(crv: &str, d: Option<&[u8]>, point: &[u8]) -> Result<Self, ConversionError> {
+        match point.split_first() {
+            Some((0x04 /* uncompressed */, coords)) if coords.len() % 2 == 0 => {
+                let (x, y) = coords.split_at(coords.len() / 2);
+                Self::from_components(crv, d, x, y).map_err(|_| invalid_der())
+            }
+            _ => Err(invalid_der()),
         }
     }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "crv")]
-pub enum Curve {
-    /// Parameters of the prime256v1 (P256) curve.
-    #[serde(rename = "P-256")]
-    P256 {
-        /// The private scalar.
-        #[serde(skip_serializing_if = "Option::is_none")]
+pub enum OkpCurve {
+    /// Parameters of the Ed25519 signature curve.
+    Ed25519 {
+        /// The private key.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
         d: Option<ByteArray<U32>>,
-        /// The curve point x coordinate.
+        /// The public key.
         x: ByteArray<U32>,
-        /// The curve point y coordinate.
-        y: ByteArray<U32>,
     },
+    /// Parameters of the Ed448 signature curve.
+    Ed448 {
+        /// The private key.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        d: Option<ByteArray<U57>>,
+        /// The public key.
+        x: ByteArray<U57>,
+    },
+    /// Parameters of the X25519 key agreement curve.
+    X25519 {
+        /// The private key.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        d: Option<ByteArray<U32>>,
+        /// The public key.
+        x: ByteArray<U32>,
+    },
+    /// Parameters of the X448 key agreement curve.
+    X448 {
+        /// The private key.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        d: Option<ByteArray<U56>>,
+        /// The public key.
+        x: ByteArray<U56>,
+    },
+}
+
+/// The `crv` names and algorithm OIDs ([RFC 8410](https://tools.ietf.org/html/rfc8410#section-3))
+/// of the supported octet key pair curves.
+#[cfg(feature = "pkcs-convert")]
+const OKP_CURVE_OIDS: &[(&str, &[u64])] = &[
+    ("Ed25519", &[1, 3, 101, 112]), // id-Ed25519
+    ("Ed448", &[1, 3, 101, 113]),   // id-Ed448
+    ("X25519", &[1, 3, 101, 110]),  // id-X25519
+    ("X448", &[1, 3, 101, 111]),    // id-X448
+];
+
+/// The `crv` names and multicodec prefixes (as unsigned varints) of the public keys supported
+/// by `did:key`.
+#[cfg(feature = "did-key")]
+const DID_KEY_CODECS: &[(&str, &[u8])] = &[
+    ("P-256", &[0x80, 0x24]),   // p256-pub (0x1200)
+    ("Ed25519", &[0xed, 0x01]), // ed25519-pub
+    ("X25519", &[0xec, 0x01]),  // x25519-pub
+];
+
+impl OkpCurve {
+    /// Returns the name of this curve, as used in the `crv` member.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ed25519 { .. } => "Ed25519",
+            Self::Ed448 { .. } => "Ed448",
+            Self::X25519 { .. } => "X25519",
+            Self::X448 { .. } => "X448",
+        }
+    }
+
+    /// Returns the size of this curve's keys in bits.
+    fn num_bits(&self) -> usize {
+        match self {
+            Self::Ed25519 { .. } => 256,
+            Self::Ed448 { .. } => 456,
+            Self::X25519 { .. } => 256,
+            Self::X448 { .. } => 448,
+        }
+    }
+
+    /// Returns true iff this curve is used for key agreement (ECDH-ES) rather than signatures.
+    fn is_key_agreement(&self) -> bool {
+        match self {
+            Self::Ed25519 { .. } | Self::Ed448 { .. } => false,
+            Self::X25519 { .. } | Self::X448 { .. } => true,
+        }
+    }
+
+    /// Returns the private key, if any, and the public key.
+    fn components(&self) -> (Option<&[u8]>, &[u8]) {
+        match self {
+            Self::Ed25519 { d, x } => (d.as_ref().map(|d| d.as_slice()), x),
+            Self::Ed448 { d, x } => (d.as_ref().map(|d| d.as_slice()), x),
+            Self::X25519 { d, x } => (d.as_ref().map(|d| d.as_slice()), x),
+            Self::X448 { d, x } => (d.as_ref().map(|d| d.as_slice()), x),
+        }
+    }
+
+    /// Returns true iff `crv` names a supported curve.
+    fn is_supported(crv: &str) -> bool {
+        matches!(crv, "Ed25519" | "Ed448" | "X25519" | "X448")
+    }
+
+    /// Constructs the parameters of the curve named `crv` from its components.
+    fn from_components(crv: &str, d: Option<&[u8]>, x: &[u8]) -> Result<Self, String> {
+        macro_rules! curve {
+            ($variant:ident) => {
+                Self::$variant {
+                    d: d.map(ByteArray::try_from_slice).transpose()?,
+                    x: ByteArray::try_from_slice(x)?,
+                }
+            };
+        }
+        Ok(match crv {
+            "Ed25519" => curve!(Ed25519),
+            "Ed448" => curve!(Ed448),
+            "X25519" => curve!(X25519),
+            "X448" => curve!(X448),
+            _ => return Err(format!("unsupported curve: {}", crv)),
+        })
+    }
+
+    /// Returns these parameters without the private key.
+    fn to_public(&self) -> Self {
+        Self::from_components(self.name(), None, self.components().1).unwrap()
+    }
+
+    /// Returns the algorithm OID of this curve.
+    #[cfg(feature = "pkcs-convert")]
+    fn oid(&self) -> &'static [u64] {
+        let name = self.name();
+        OKP_CURVE_OIDS.iter().find(|(n, _)| *n == name).unwrap().1
+    }
+
+    /// Returns the name of the curve having the provided algorithm OID.
+    #[cfg(feature = "pkcs-convert")]
+    fn name_for_oid(oid: &[u64]) -> Option<&'static str> {
+        OKP_CURVE_OIDS
+            .iter()
+            .find(|(_, curve_oid)| *curve_oid == oid)
+            .map(|(name, _)| *name)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -419,6 +2251,8 @@ pub struct RsaPublic {
     /// The standard public exponent, 65537.
     pub e: PublicExponent,
     /// The modulus, p*q.
+    /// The decoded bytes are kept verbatim, including any leading zeros, so that re-serializing
+    /// a parsed key yields the same value and thumbprint as the original.
     pub n: ByteVec,
 }
 
@@ -442,7 +2276,7 @@ impl<'de> Deserialize<'de> for PublicExponent {
         if e == PUBLIC_EXPONENT_B64 || e == PUBLIC_EXPONENT_B64_PADDED {
             Ok(Self)
         } else {
-            Err(serde::de::Error::custom(&format!(
+            Err(serde::de::Error::custom(format!(
                 "public exponent must be {}",
                 PUBLIC_EXPONENT
             )))
@@ -471,6 +2305,90 @@ pub struct RsaPrivate {
     pub qi: Option<ByteVec>,
 }
 
+/// The raw public key material of an asymmetric key, as returned by `Key::to_raw_public_bytes`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RawPublicKey {
+    /// The uncompressed SEC1 encoding of the public point, `0x04 || x || y`.
+    Ec(Vec<u8>),
+    /// The public key of an OKP curve (e.g., the 32-byte `x` of an Ed25519 key).
+    Okp(Vec<u8>),
+    /// The big-endian modulus and public exponent.
+    Rsa { n: Vec<u8>, e: Vec<u8> },
+}
+
+/// Options for writing PEM using `Key::try_to_pem_with`. The default options match those of
+/// `Key::try_to_pem`.
+#[cfg(feature = "pkcs-convert")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PemOptions {
+    /// The number of base64 characters per line, or zero to not wrap the base64.
+    pub line_width: usize,
+    pub line_ending: LineEnding,
+    pub label: PemLabel,
+}
+
+#[cfg(feature = "pkcs-convert")]
+impl Default for PemOptions {
+    fn default() -> Self {
+        Self {
+            line_width: 64,
+            line_ending: LineEnding::Lf,
+            label: PemLabel::Pkcs8,
+        }
+    }
+}
+
+#[cfg(feature = "pkcs-convert")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineEnding {
+    Lf,
+    CrLf,
+}
+
+/// The encoding, and hence the label, of a PEM block.
+#[cfg(feature = "pkcs-convert")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PemLabel {
+    /// PKCS#8 `PRIVATE KEY` or SubjectPublicKeyInfo `PUBLIC KEY`.
+    Pkcs8,
+    /// PKCS#1 `RSA PRIVATE KEY` or `RSA PUBLIC KEY`, or SEC1 `EC PRIVATE KEY`, where applicable.
+    TypeSpecific,
+}
+
+/// A hash function with which to compute key thumbprints.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HashAlg {
+    /// SHA-1, only for compatibility with legacy systems such as `x5t` consumers.
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+/// Parameters of the PBES2 password-based encryption of PKCS#8 keys, using PBKDF2 and AES-CBC.
+#[cfg(feature = "crypto")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pbes2Params {
+    /// The number of PBKDF2 iterations, at most `jwe::PBES2_MAX_ITERATIONS`.
+    pub iterations: u32,
+    /// The hash function of the HMAC used as the PBKDF2 pseudorandom function.
+    pub prf: HashAlg,
+    /// The length, in bytes, of the AES key: 16, 24, or 32.
+    pub key_len: usize,
+}
+
+#[cfg(feature = "crypto")]
+impl Default for Pbes2Params {
+    /// PBKDF2-HMAC-SHA256 with 600,000 iterations, as recommended by OWASP, and AES-256-CBC.
+    fn default() -> Self {
+        Self {
+            iterations: 600_000,
+            prf: HashAlg::Sha256,
+            key_len: 32,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum KeyUse {
     #[serde(rename = "sig")]
@@ -479,24 +2397,277 @@ pub enum KeyUse {
     Encryption,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(remote = "Self")]
 pub enum Algorithm {
     HS256,
+    HS384,
+    HS512,
     RS256,
+    RS384,
+    RS512,
+    PS256,
+    PS384,
+    PS512,
     ES256,
+    ES384,
+    ES512,
+    ES256K,
+    EdDSA,
+    RSA1_5,
+    #[serde(rename = "RSA-OAEP")]
+    RsaOaep,
+    #[serde(rename = "RSA-OAEP-256")]
+    RsaOaep256,
+    A128KW,
+    A192KW,
+    A256KW,
+    #[serde(rename = "dir")]
+    Dir,
+    #[serde(rename = "ECDH-ES")]
+    EcdhEs,
+    #[serde(rename = "ECDH-ES+A128KW")]
+    EcdhEsA128KW,
+    #[serde(rename = "ECDH-ES+A192KW")]
+    EcdhEsA192KW,
+    #[serde(rename = "ECDH-ES+A256KW")]
+    EcdhEsA256KW,
+    A128GCMKW,
+    A192GCMKW,
+    A256GCMKW,
+    #[serde(rename = "PBES2-HS256+A128KW")]
+    Pbes2Hs256A128KW,
+    #[serde(rename = "PBES2-HS384+A192KW")]
+    Pbes2Hs384A192KW,
+    #[serde(rename = "PBES2-HS512+A256KW")]
+    Pbes2Hs512A256KW,
+    A128GCM,
+    A192GCM,
+    A256GCM,
+    #[serde(rename = "A128CBC-HS256")]
+    A128CbcHs256,
+    #[serde(rename = "A192CBC-HS384")]
+    A192CbcHs384,
+    #[serde(rename = "A256CBC-HS512")]
+    A256CbcHs512,
+    /// An algorithm which is not supported by this crate, kept verbatim. Keys having such an
+    /// `alg` are not validated.
+    #[serde(skip)]
+    Other(String),
+}
+
+impl Serialize for Algorithm {
+    fn serialize<S: serde::ser::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Other(alg) => alg.serialize(s),
+            _ => Self::serialize(self, s),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Algorithm {
+    fn deserialize<D: serde::de::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let alg = String::deserialize(d)?;
+        Ok(alg.parse().unwrap())
+    }
+}
+
+impl std::str::FromStr for Algorithm {
+    type Err = std::convert::Infallible;
+
+    /// Parses an `alg` (or `enc`) value. Unrecognized values become `Algorithm::Other`.
+    fn from_str(alg: &str) -> Result<Self, Self::Err> {
+        Ok(Self::deserialize(serde_json::Value::String(alg.into()))
+            .unwrap_or_else(|_| Self::Other(alg.into())))
+    }
+}
+
+impl std::fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match serde_json::to_value(self) {
+            Ok(serde_json::Value::String(alg)) => f.write_str(&alg),
+            _ => unreachable!("algorithms serialize as strings"),
+        }
+    }
+}
+
+impl Algorithm {
+    /// Returns the `kty` values of the keys which may be used with this algorithm.
+    /// ECDH-ES may be used with both `EC` and `OKP` keys; unknown algorithms have no key types.
+    pub fn key_type(&self) -> &'static [&'static str] {
+        match self.family() {
+            AlgorithmFamily::Hmac
+            | AlgorithmFamily::AesKeyWrap
+            | AlgorithmFamily::Direct
+            | AlgorithmFamily::Pbes2
+            | AlgorithmFamily::AesGcm
+            | AlgorithmFamily::AesCbcHmac => &["oct"],
+            AlgorithmFamily::Rsa | AlgorithmFamily::RsaPss | AlgorithmFamily::RsaEs => &["RSA"],
+            AlgorithmFamily::Ecdsa => &["EC"],
+            AlgorithmFamily::Eddsa => &["OKP"],
+            AlgorithmFamily::EcdhEs => &["EC", "OKP"],
+            AlgorithmFamily::Unknown => &[],
+        }
+    }
+
+    /// Returns true iff this is a JWS (signature or MAC) algorithm.
+    pub fn is_signing(&self) -> bool {
+        match self {
+            Self::HS256
+            | Self::HS384
+            | Self::HS512
+            | Self::RS256
+            | Self::RS384
+            | Self::RS512
+            | Self::PS256
+            | Self::PS384
+            | Self::PS512
+            | Self::ES256
+            | Self::ES384
+            | Self::ES512
+            | Self::ES256K
+            | Self::EdDSA => true,
+            Self::RSA1_5
+            | Self::RsaOaep
+            | Self::RsaOaep256
+            | Self::A128KW
+            | Self::A192KW
+            | Self::A256KW
+            | Self::Dir
+            | Self::EcdhEs
+            | Self::EcdhEsA128KW
+            | Self::EcdhEsA192KW
+            | Self::EcdhEsA256KW
+            | Self::A128GCMKW
+            | Self::A192GCMKW
+            | Self::A256GCMKW
+            | Self::Pbes2Hs256A128KW
+            | Self::Pbes2Hs384A192KW
+            | Self::Pbes2Hs512A256KW
+            | Self::A128GCM
+            | Self::A192GCM
+            | Self::A256GCM
+            | Self::A128CbcHs256
+            | Self::A192CbcHs384
+            | Self::A256CbcHs512
+            | Self::Other(_) => false,
+        }
+    }
+
+    /// Returns the length in bytes of the symmetric key used with this algorithm, or `None` if
+    /// it doesn't use a symmetric key of fixed length.
+    #[cfg(feature = "generate")]
+    fn symmetric_key_len(&self) -> Option<usize> {
+        Some(match self {
+            Self::HS256 => 32,
+            Self::HS384 => 48,
+            Self::HS512 => 64,
+            Self::A128KW | Self::A128GCMKW => 16,
+            Self::A192KW | Self::A192GCMKW => 24,
+            Self::A256KW | Self::A256GCMKW => 32,
+            _ => return self.content_key_len(),
+        })
+    }
+
+    /// Returns the key length in bytes required by this content encryption (`enc`) algorithm,
+    /// or `None` if this is not a content encryption algorithm.
+    fn content_key_len(&self) -> Option<usize> {
+        Some(match self {
+            Self::A128GCM => 16,
+            Self::A192GCM => 24,
+            Self::A256GCM | Self::A128CbcHs256 => 32,
+            Self::A192CbcHs384 => 48,
+            Self::A256CbcHs512 => 64,
+            _ => return None,
+        })
+    }
+
+    /// Returns the broad family to which this algorithm belongs.
+    pub fn family(&self) -> AlgorithmFamily {
+        match self {
+            Self::HS256 | Self::HS384 | Self::HS512 => AlgorithmFamily::Hmac,
+            Self::RS256 | Self::RS384 | Self::RS512 => AlgorithmFamily::Rsa,
+            Self::PS256 | Self::PS384 | Self::PS512 => AlgorithmFamily::RsaPss,
+            Self::ES256 | Self::ES384 | Self::ES512 | Self::ES256K => AlgorithmFamily::Ecdsa,
+            Self::EdDSA => AlgorithmFamily::Eddsa,
+            Self::RSA1_5 | Self::RsaOaep | Self::RsaOaep256 => AlgorithmFamily::RsaEs,
+            Self::A128KW
+            | Self::A192KW
+            | Self::A256KW
+            | Self::A128GCMKW
+            | Self::A192GCMKW
+            | Self::A256GCMKW => AlgorithmFamily::AesKeyWrap,
+            Self::Dir => AlgorithmFamily::Direct,
+            Self::EcdhEs | Self::EcdhEsA128KW | Self::EcdhEsA192KW | Self::EcdhEsA256KW => {
+                AlgorithmFamily::EcdhEs
+            }
+            Self::Pbes2Hs256A128KW | Self::Pbes2Hs384A192KW | Self::Pbes2Hs512A256KW => {
+                AlgorithmFamily::Pbes2
+            }
+            Self::A128GCM | Self::A192GCM | Self::A256GCM => AlgorithmFamily::AesGcm,
+            Self::A128CbcHs256 | Self::A192CbcHs384 | Self::A256CbcHs512 => {
+                AlgorithmFamily::AesCbcHmac
+            }
+            Self::Other(_) => AlgorithmFamily::Unknown,
+        }
+    }
+}
+
+/// A broad classification of algorithms, for use when the specific algorithm is unimportant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AlgorithmFamily {
+    /// HMAC using SHA-2 (HS*).
+    Hmac,
+    /// RSASSA-PKCS1-v1_5 (RS*).
+    Rsa,
+    /// ECDSA (ES*).
+    Ecdsa,
+    /// RSASSA-PSS (PS*).
+    RsaPss,
+    /// Edwards-curve signatures (EdDSA).
+    Eddsa,
+    /// Elliptic Curve Diffie-Hellman Ephemeral Static key agreement (ECDH-ES*).
+    EcdhEs,
+    /// RSA key encryption (RSA1_5 and RSA-OAEP*).
+    RsaEs,
+    /// AES key wrapping (A*KW and A*GCMKW).
+    AesKeyWrap,
+    /// Direct use of a shared symmetric key as the content encryption key (dir).
+    Direct,
+    /// Password-based key wrapping (PBES2-*).
+    Pbes2,
+    /// AES-GCM content encryption (A*GCM).
+    AesGcm,
+    /// AES-CBC with HMAC-SHA-2 content encryption (A*CBC-HS*).
+    AesCbcHmac,
+    /// The family of an unknown algorithm, or of a key of unknown type having no `alg`.
+    Unknown,
 }
 
 #[cfg(feature = "jwt-convert")]
-const _IMPL_JWT_CONVERSIONS: () = {
+const _: () = {
     use jsonwebtoken as jwt;
 
-    impl Into<jwt::Algorithm> for Algorithm {
-        fn into(self) -> jsonwebtoken::Algorithm {
-            match self {
-                Self::HS256 => jwt::Algorithm::HS256,
-                Self::ES256 => jwt::Algorithm::ES256,
-                Self::RS256 => jwt::Algorithm::RS256,
-            }
+    /// Fails with `ConversionError::UnsupportedAlgorithm` for algorithms `jsonwebtoken` lacks.
+    /// This replaces the `From` impl of jsonwebkey 0.2, which predates such algorithms.
+    impl std::convert::TryFrom<Algorithm> for jwt::Algorithm {
+        type Error = ConversionError;
+
+        fn try_from(alg: Algorithm) -> Result<Self, Self::Error> {
+            Ok(match alg {
+                Algorithm::HS256 => Self::HS256,
+                Algorithm::HS384 => Self::HS384,
+                Algorithm::HS512 => Self::HS512,
+                Algorithm::ES256 => Self::ES256,
+                Algorithm::ES384 => Self::ES384,
+                Algorithm::RS256 => Self::RS256,
+                Algorithm::RS384 => Self::RS384,
+                Algorithm::RS512 => Self::RS512,
+                Algorithm::PS256 => Self::PS256,
+                Algorithm::PS384 => Self::PS384,
+                Algorithm::PS512 => Self::PS512,
+                _ => return Err(ConversionError::UnsupportedAlgorithm),
+            })
         }
     }
 
@@ -516,6 +2687,9 @@ const _IMPL_JWT_CONVERSIONS: () = {
                 Self::RSA { .. } => {
                     jwt::EncodingKey::from_rsa_pem(self.try_to_pem()?.as_bytes()).unwrap()
                 }
+                Self::OKP { .. } | Self::Unknown { .. } => {
+                    return Err(ConversionError::UnsupportedKey)
+                }
             })
         }
 
@@ -524,23 +2698,34 @@ const _IMPL_JWT_CONVERSIONS: () = {
             self.try_to_encoding_key().unwrap()
         }
 
-        pub fn to_decoding_key(&self) -> jwt::DecodingKey<'static> {
-            match self {
+        /// Returns a `DecodingKey` if the key type is supported by `jsonwebtoken`.
+        pub fn try_to_decoding_key(&self) -> Result<jwt::DecodingKey<'static>, ConversionError> {
+            Ok(match self {
                 Self::Symmetric { key } => {
                     jwt::DecodingKey::from_secret(key.0.as_slice()).into_static()
                 }
                 Self::EC { .. } => {
                     // The following will not panic: all EC JWKs have public components due to
-                    // typing. PEM conversion will always succeed, for the same reason.
+                    // typing, and PEM conversion only fails for unknown curves.
                     // Hence, jwt::DecodingKey shall have no issue with de-converting.
-                    jwt::DecodingKey::from_ec_pem(self.to_public().unwrap().to_pem().as_bytes())
+                    let pem = self.to_public().unwrap().try_to_pem()?;
+                    jwt::DecodingKey::from_ec_pem(pem.as_bytes())
                         .unwrap()
                         .into_static()
                 }
                 Self::RSA { .. } => jwt::DecodingKey::from_rsa_pem(self.to_pem().as_bytes())
                     .unwrap()
                     .into_static(),
-            }
+                Self::OKP { .. } | Self::Unknown { .. } => {
+                    return Err(ConversionError::UnsupportedKey)
+                }
+            })
+        }
+
+        /// Unwrapping `try_to_decoding_key`. Panics if the key is an octet key pair or is of
+        /// an unknown type or curve.
+        pub fn to_decoding_key(&self) -> jwt::DecodingKey<'static> {
+            self.try_to_decoding_key().unwrap()
         }
     }
 };
@@ -555,6 +2740,72 @@ pub enum Error {
 
     #[error("mismatched algorithm for key type")]
     MismatchedAlgorithm,
+
+    #[error("invalid did:jwk identifier")]
+    InvalidDidJwk,
+
+    #[cfg(feature = "pkcs-convert")]
+    #[error("the x5c certificate does not contain the key")]
+    CertificateMismatch,
+
+    #[cfg(feature = "crypto")]
+    #[error("invalid certificate chain: {0}")]
+    InvalidCertificateChain(&'static str),
+
+    #[cfg(feature = "crypto")]
+    #[error("invalid signature")]
+    InvalidSignature,
+
+    #[cfg(feature = "crypto")]
+    #[error("the key has no algorithm")]
+    MissingAlgorithm,
+
+    #[cfg(feature = "crypto")]
+    #[error("invalid or unsupported JWS")]
+    InvalidJws,
+
+    #[cfg(feature = "crypto")]
+    #[error("invalid or unsupported JWE")]
+    InvalidJwe,
+
+    #[cfg(feature = "crypto")]
+    #[error("invalid JWT claims: {0}")]
+    InvalidClaims(&'static str),
+
+    #[cfg(feature = "crypto")]
+    #[error("the peer public key is invalid or not on the curve of the private key")]
+    InvalidPublicKey,
+
+    #[cfg(feature = "crypto")]
+    #[error("decryption failed")]
+    DecryptionFailed,
+
+    #[error(transparent)]
+    Conversion(#[from] ConversionError),
+
+    #[cfg(feature = "jwt-convert")]
+    #[error(transparent)]
+    Jwt(#[from] jsonwebtoken::errors::Error),
+
+    #[cfg(feature = "generate")]
+    #[error("symmetric key size must be a non-zero multiple of 8 bits, but was {0}")]
+    InvalidKeySize(usize),
+
+    #[cfg(feature = "generate")]
+    #[error("the key use, operations, and algorithm are inconsistent")]
+    InconsistentUsage,
+
+    #[cfg(feature = "generate")]
+    #[error("the key store file was modified by another process")]
+    KeyStoreModified,
+
+    #[cfg(feature = "generate")]
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[cfg(feature = "did-key")]
+    #[error("invalid or unsupported did:key identifier")]
+    InvalidDidKey,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -565,7 +2816,38 @@ pub enum ConversionError {
     #[error("a symmetric key can not be encoded using PKCS#8")]
     NotAsymmetric,
 
-    #[cfg(feature = "jwt-convert")]
-    #[error("a public key cannot be converted to a `jsonwebtoken::EncodingKey`")]
+    #[error("the key algorithm or parameters are not supported")]
+    UnsupportedKey,
+
+    #[cfg(feature = "pkcs-convert")]
+    #[error("invalid DER: {0}")]
+    InvalidDer(#[from] yasna::ASN1Error),
+
+    #[error("invalid PEM")]
+    InvalidPem,
+
+    #[error("invalid OpenSSH key")]
+    InvalidOpenSsh,
+
+    #[cfg(feature = "cose")]
+    #[error("invalid COSE_Key")]
+    InvalidCose,
+
+    #[error("invalid ECDSA signature")]
+    InvalidEcdsaSignature,
+
+    #[error("invalid key component: {0}")]
+    InvalidLength(String),
+
+    #[cfg(feature = "pkcs-convert")]
+    #[error("the operation requires a private key")]
     NotPrivate,
+
+    #[cfg(feature = "jwt-convert")]
+    #[error("the algorithm is not supported by `jsonwebtoken`")]
+    UnsupportedAlgorithm,
+
+    #[cfg(feature = "crypto")]
+    #[error("decryption failed; the password may be incorrect")]
+    DecryptionFailed,
 }
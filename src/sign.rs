@@ -0,0 +1,146 @@
+use ring::{hmac, signature};
+
+use crate::{Algorithm, ConversionError, Curve, Error, JsonWebKey, Key, OkpCurve, RsaPublic};
+
+impl Key {
+    /// Signs (or MACs) `message` using `alg`, returning the signature in its JWS form, e.g. the
+    /// fixed-size `r || s` concatenation for ECDSA.
+    /// Supports the HS, RS, and PS algorithms, ES256 and ES384, and EdDSA with Ed25519 keys.
+    pub fn sign(&self, alg: &Algorithm, message: &[u8]) -> Result<Vec<u8>, Error> {
+        use Algorithm::*;
+
+        // `ring` errors carry no detail beyond the key being unusable.
+        fn unsupported_key<E>(_: E) -> Error {
+            ConversionError::UnsupportedKey.into()
+        }
+
+        JsonWebKey::validate_algorithm(alg, self)?;
+        if !self.is_private() {
+            return Err(ConversionError::NotPrivate.into());
+        }
+        let rng = ring::rand::SystemRandom::new();
+        let signature = match (alg, self) {
+            (HS256 | HS384 | HS512, Self::Symmetric { key }) => {
+                hmac::sign(&hmac::Key::new(hmac_algorithm(alg), key), message)
+                    .as_ref()
+                    .to_vec()
+            }
+            (RS256 | RS384 | RS512 | PS256 | PS384 | PS512, Self::RSA { .. }) => {
+                let padding: &dyn signature::RsaEncoding = match alg {
+                    RS256 => &signature::RSA_PKCS1_SHA256,
+                    RS384 => &signature::RSA_PKCS1_SHA384,
+                    RS512 => &signature::RSA_PKCS1_SHA512,
+                    PS256 => &signature::RSA_PSS_SHA256,
+                    PS384 => &signature::RSA_PSS_SHA384,
+                    _ => &signature::RSA_PSS_SHA512,
+                };
+                let key_pair = signature::RsaKeyPair::from_pkcs8(&self.try_to_der()?)
+                    .map_err(unsupported_key)?;
+                let mut sig = vec![0; key_pair.public_modulus_len()];
+                key_pair
+                    .sign(padding, &rng, message, &mut sig)
+                    .map_err(unsupported_key)?;
+                sig
+            }
+            (ES256 | ES384, Self::EC { curve }) => {
+                let (sign_alg, d, x, y): (_, &[u8], &[u8], &[u8]) = match curve {
+                    Curve::P256 { d: Some(d), x, y } => {
+                        (&signature::ECDSA_P256_SHA256_FIXED_SIGNING, d, x, y)
+                    }
+                    Curve::P384 { d: Some(d), x, y } => {
+                        (&signature::ECDSA_P384_SHA384_FIXED_SIGNING, d, x, y)
+                    }
+                    _ => return Err(ConversionError::UnsupportedKey.into()),
+                };
+                let public = [&[0x04][..], x, y].concat();
+                let key_pair =
+                    signature::EcdsaKeyPair::from_private_key_and_public_key(sign_alg, d, &public)
+                        .map_err(unsupported_key)?;
+                key_pair
+                    .sign(&rng, message)
+                    .map_err(unsupported_key)?
+                    .as_ref()
+                    .to_vec()
+            }
+            (
+                EdDSA,
+                Self::OKP {
+                    curve: OkpCurve::Ed25519 { d: Some(d), x },
+                },
+            ) => signature::Ed25519KeyPair::from_seed_and_public_key(d, x)
+                .map_err(unsupported_key)?
+                .sign(message)
+                .as_ref()
+                .to_vec(),
+            _ => return Err(ConversionError::UnsupportedKey.into()),
+        };
+        Ok(signature)
+    }
+
+    /// Verifies the JWS signature (or MAC) `signature` of `message` made using `alg`.
+    /// Supports the same algorithms as `sign`, and returns `Error::InvalidSignature` if the
+    /// signature doesn't match.
+    pub fn verify(&self, alg: &Algorithm, message: &[u8], signature: &[u8]) -> Result<(), Error> {
+        use Algorithm::*;
+
+        JsonWebKey::validate_algorithm(alg, self)?;
+        let invalid = |_| Error::InvalidSignature;
+        match (alg, self) {
+            (HS256 | HS384 | HS512, Self::Symmetric { key }) => hmac::verify(
+                &hmac::Key::new(hmac_algorithm(alg), key),
+                message,
+                signature,
+            )
+            .map_err(invalid),
+            (
+                RS256 | RS384 | RS512 | PS256 | PS384 | PS512,
+                Self::RSA {
+                    public: RsaPublic { n, .. },
+                    ..
+                },
+            ) => {
+                let params: &signature::RsaParameters = match alg {
+                    RS256 => &signature::RSA_PKCS1_2048_8192_SHA256,
+                    RS384 => &signature::RSA_PKCS1_2048_8192_SHA384,
+                    RS512 => &signature::RSA_PKCS1_2048_8192_SHA512,
+                    PS256 => &signature::RSA_PSS_2048_8192_SHA256,
+                    PS384 => &signature::RSA_PSS_2048_8192_SHA384,
+                    _ => &signature::RSA_PSS_2048_8192_SHA512,
+                };
+                let public = signature::RsaPublicKeyComponents {
+                    n: &n[..],
+                    e: &[0x01, 0x00, 0x01][..],
+                };
+                public.verify(params, message, signature).map_err(invalid)
+            }
+            (ES256 | ES384, Self::EC { curve }) => {
+                let (verify_alg, x, y): (_, &[u8], &[u8]) = match curve {
+                    Curve::P256 { x, y, .. } => (&signature::ECDSA_P256_SHA256_FIXED, x, y),
+                    Curve::P384 { x, y, .. } => (&signature::ECDSA_P384_SHA384_FIXED, x, y),
+                    _ => return Err(ConversionError::UnsupportedKey.into()),
+                };
+                let public = [&[0x04][..], x, y].concat();
+                signature::UnparsedPublicKey::new(verify_alg, public)
+                    .verify(message, signature)
+                    .map_err(invalid)
+            }
+            (
+                EdDSA,
+                Self::OKP {
+                    curve: OkpCurve::Ed25519 { x, .. },
+                },
+            ) => signature::UnparsedPublicKey::new(&signature::ED25519, &**x)
+                .verify(message, signature)
+                .map_err(invalid),
+            _ => Err(ConversionError::UnsupportedKey.into()),
+        }
+    }
+}
+
+fn hmac_algorithm(alg: &Algorithm) -> hmac::Algorithm {
+    match alg {
+        Algorithm::HS384 => hmac::HMAC_SHA384,
+        Algorithm::HS512 => hmac::HMAC_SHA512,
+        _ => hmac::HMAC_SHA256,
+    }
+}
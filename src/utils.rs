@@ -12,6 +12,11 @@ fn base64_encode(bytes: impl AsRef<[u8]>) -> String {
     base64::encode_config(bytes, base64_config())
 }
 
+/// Encodes `bytes` as base64url without padding, as required by RFC 7638 thumbprints.
+pub fn base64_encode_unpadded(bytes: impl AsRef<[u8]>) -> String {
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
 fn base64_decode(b64: impl AsRef<[u8]>) -> Result<Vec<u8>, base64::DecodeError> {
     base64::decode_config(b64, base64_config())
 }
@@ -31,12 +36,202 @@ pub fn deserialize_base64<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<u8>, D:
     })
 }
 
+/// The JSON Canonicalization Scheme of [RFC 8785](https://tools.ietf.org/html/rfc8785).
+pub mod jcs {
+    use std::fmt::Write;
+
+    use serde_json::Value;
+
+    pub fn to_string(value: &Value) -> String {
+        let mut jcs = String::new();
+        write_value(&mut jcs, value);
+        jcs
+    }
+
+    fn write_value(jcs: &mut String, value: &Value) {
+        match value {
+            Value::Null => jcs.push_str("null"),
+            Value::Bool(b) => jcs.push_str(if *b { "true" } else { "false" }),
+            Value::Number(n) => write_number(jcs, n.as_f64().unwrap()),
+            Value::String(s) => write_string(jcs, s),
+            Value::Array(values) => {
+                jcs.push('[');
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        jcs.push(',');
+                    }
+                    write_value(jcs, value);
+                }
+                jcs.push(']');
+            }
+            Value::Object(members) => {
+                // Members are sorted by the UTF-16 code units of their names.
+                let mut members: Vec<_> = members.iter().collect();
+                members.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+                jcs.push('{');
+                for (i, (name, value)) in members.into_iter().enumerate() {
+                    if i > 0 {
+                        jcs.push(',');
+                    }
+                    write_string(jcs, name);
+                    jcs.push(':');
+                    write_value(jcs, value);
+                }
+                jcs.push('}');
+            }
+        }
+    }
+
+    fn write_string(jcs: &mut String, s: &str) {
+        jcs.push('"');
+        for c in s.chars() {
+            match c {
+                '"' => jcs.push_str("\\\""),
+                '\\' => jcs.push_str("\\\\"),
+                '\u{8}' => jcs.push_str("\\b"),
+                '\t' => jcs.push_str("\\t"),
+                '\n' => jcs.push_str("\\n"),
+                '\u{c}' => jcs.push_str("\\f"),
+                '\r' => jcs.push_str("\\r"),
+                c if c < ' ' => write!(jcs, "\\u{:04x}", c as u32).unwrap(),
+                c => jcs.push(c),
+            }
+        }
+        jcs.push('"');
+    }
+
+    /// Writes `n` as ECMAScript's `Number.prototype.toString` would
+    /// ([ECMA-262 §7.1.12.1](https://262.ecma-international.org/10.0/#sec-tostring-applied-to-the-number-type)).
+    fn write_number(jcs: &mut String, n: f64) {
+        if n == 0.0 {
+            jcs.push('0');
+            return;
+        }
+        if n < 0.0 {
+            jcs.push('-');
+        }
+        // Rust's `{:e}` formatting yields the shortest digits that round-trip, as does ECMAScript.
+        // Of the candidates having that many digits, ECMAScript picks the one closest to `n`
+        // (ties going to even), which is what exact formatting to that precision yields.
+        let shortest = format!("{:e}", n.abs());
+        let num_digits = shortest.find('e').unwrap() - shortest.find('.').map_or(0, |_| 1);
+        let nearest = format!("{:.*e}", num_digits - 1, n.abs());
+        let sci = if nearest.parse() == Ok(n.abs()) {
+            nearest
+        } else {
+            shortest
+        };
+        let (mantissa, exponent) = sci.split_at(sci.find('e').unwrap());
+        let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+        let k = digits.len() as i32;
+        let n = exponent[1..].parse::<i32>().unwrap() + 1;
+        if k <= n && n <= 21 {
+            jcs.push_str(&digits);
+            jcs.push_str(&"0".repeat((n - k) as usize));
+        } else if 0 < n && n <= 21 {
+            let (int, frac) = digits.split_at(n as usize);
+            write!(jcs, "{}.{}", int, frac).unwrap();
+        } else if -6 < n && n <= 0 {
+            jcs.push_str("0.");
+            jcs.push_str(&"0".repeat(-n as usize));
+            jcs.push_str(&digits);
+        } else {
+            let (first, rest) = digits.split_at(1);
+            jcs.push_str(first);
+            if !rest.is_empty() {
+                write!(jcs, ".{}", rest).unwrap();
+            }
+            write!(jcs, "e{}{}", if n > 0 { '+' } else { '-' }, (n - 1).abs()).unwrap();
+        }
+    }
+}
+
+/// Base58 using the Bitcoin alphabet, as used by the `z` (base58btc) multibase prefix.
+#[cfg(feature = "did-key")]
+pub mod base58 {
+    const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+    pub fn encode(bytes: &[u8]) -> String {
+        // Little-endian base 58 digits of the big-endian number `bytes`.
+        let mut digits: Vec<u8> = Vec::new();
+        for byte in bytes {
+            let mut carry = *byte as usize;
+            for digit in digits.iter_mut() {
+                carry += (*digit as usize) << 8;
+                *digit = (carry % 58) as u8;
+                carry /= 58;
+            }
+            while carry > 0 {
+                digits.push((carry % 58) as u8);
+                carry /= 58;
+            }
+        }
+        // Each leading zero byte is encoded as a leading `1`.
+        let zeros = bytes.iter().take_while(|b| **b == 0).count();
+        let digits = digits
+            .iter()
+            .rev()
+            .map(|d| char::from(ALPHABET[*d as usize]));
+        "1".repeat(zeros) + &digits.collect::<String>()
+    }
+
+    pub fn decode(b58: &str) -> Option<Vec<u8>> {
+        // Little-endian bytes of the number `b58`.
+        let mut bytes: Vec<u8> = Vec::new();
+        for c in b58.bytes() {
+            let mut carry = ALPHABET.iter().position(|a| *a == c)?;
+            for byte in bytes.iter_mut() {
+                carry += *byte as usize * 58;
+                *byte = carry as u8;
+                carry >>= 8;
+            }
+            while carry > 0 {
+                bytes.push(carry as u8);
+                carry >>= 8;
+            }
+        }
+        let zeros = b58.bytes().take_while(|c| *c == b'1').count();
+        bytes.resize(bytes.len() + zeros, 0);
+        bytes.reverse();
+        Some(bytes)
+    }
+}
+
+/// X25519 ([RFC 7748](https://tools.ietf.org/html/rfc7748)) and Ed25519 public key derivation
+/// ([RFC 8032 §5.1.5](https://tools.ietf.org/html/rfc8032#section-5.1.5)), using the
+/// [x25519-dalek](https://crates.io/crates/x25519-dalek) and [ring](https://crates.io/crates/ring)
+/// crates.
+#[cfg(any(feature = "generate", feature = "crypto"))]
+pub mod curve25519 {
+    /// Computes the X25519 function of the `scalar` and u-coordinate `u`.
+    pub fn x25519(scalar: &[u8; 32], u: &[u8; 32]) -> [u8; 32] {
+        x25519_dalek::x25519(*scalar, *u)
+    }
+
+    /// Returns the X25519 public key of the private `scalar`.
+    pub fn x25519_public(scalar: &[u8; 32]) -> [u8; 32] {
+        x25519(scalar, &x25519_dalek::X25519_BASEPOINT_BYTES)
+    }
+
+    /// Returns the Ed25519 public key of the private key `seed`.
+    pub fn ed25519_public(seed: &[u8; 32]) -> [u8; 32] {
+        use ring::signature::{Ed25519KeyPair, KeyPair};
+
+        // Any 32 bytes are a valid seed.
+        let key_pair = Ed25519KeyPair::from_seed_unchecked(seed).unwrap();
+        let mut public = [0; 32];
+        public.copy_from_slice(key_pair.public_key().as_ref());
+        public
+    }
+}
+
 #[cfg(feature = "pkcs-convert")]
 pub mod pkcs8 {
-    use yasna::{
-        models::{ObjectIdentifier, TaggedDerValue},
-        DERWriter, DERWriterSeq,
-    };
+    use yasna::{models::ObjectIdentifier, ASN1Result, BERReaderSeq, DERWriter, DERWriterSeq, Tag};
+    use zeroize::Zeroizing;
+
+    /// The algorithm OID and the (curve) parameters OID, if any, of an `AlgorithmIdentifier`.
+    pub type AlgorithmOids = (ObjectIdentifier, Option<ObjectIdentifier>);
 
     fn write_oids(writer: &mut DERWriterSeq, oids: &[Option<&ObjectIdentifier>]) {
         for oid in oids {
@@ -47,21 +242,28 @@ pub mod pkcs8 {
         }
     }
 
-    pub fn write_private(
+    /// Writes a `PrivateKeyInfo` whose private key is the already-encoded `body`.
+    /// If `public_key` is given, a v2 `OneAsymmetricKey` including it is written instead
+    /// ([RFC 5958](https://tools.ietf.org/html/rfc5958#section-2)).
+    pub fn write_private_bytes(
         oids: &[Option<&ObjectIdentifier>],
-        body_writer: impl FnOnce(&mut DERWriterSeq),
+        body: &[u8],
+        public_key: Option<&[u8]>,
     ) -> Vec<u8> {
         yasna::construct_der(|writer| {
             writer.write_sequence(|writer| {
-                writer.next().write_i8(0); // version
+                writer.next().write_i8(public_key.is_some() as i8); // version
                 writer
                     .next()
                     .write_sequence(|writer| write_oids(writer, oids));
-
-                let body = yasna::construct_der(|writer| writer.write_sequence(body_writer));
-                writer
-                    .next()
-                    .write_tagged_der(&TaggedDerValue::from_octetstring(body));
+                writer.next().write_bytes(body);
+                if let Some(public_key) = public_key {
+                    writer
+                        .next()
+                        .write_tagged_implicit(Tag::context(1), |writer| {
+                            writer.write_bitvec_bytes(public_key, public_key.len() * 8)
+                        });
+                }
             })
         })
     }
@@ -79,4 +281,778 @@ pub mod pkcs8 {
             })
         })
     }
+
+    fn read_oids(reader: &mut BERReaderSeq) -> ASN1Result<AlgorithmOids> {
+        let algorithm = reader.next().read_oid()?;
+        let params = reader.read_optional(|reader| reader.read_oid())?;
+        if params.is_none() {
+            reader.read_optional(|reader| reader.read_null())?;
+        }
+        Ok((algorithm, params))
+    }
+
+    /// A decoded `PrivateKeyInfo` (or v2 `OneAsymmetricKey`).
+    pub struct PrivateKeyInfo {
+        pub oids: AlgorithmOids,
+        /// The encoded private key.
+        pub body: Zeroizing<Vec<u8>>,
+        /// The public key, if present (v2 only).
+        pub public_key: Option<Vec<u8>>,
+    }
+
+    /// Reads a `PrivateKeyInfo`, returning its algorithm and the encoded private key.
+    pub fn read_private(der: &[u8]) -> ASN1Result<PrivateKeyInfo> {
+        yasna::parse_der(der, |reader| {
+            reader.read_sequence(|reader| {
+                reader.next().read_u8()?; // version
+                let oids = reader.next().read_sequence(read_oids)?;
+                let body = Zeroizing::new(reader.next().read_bytes()?);
+                // Skip the optional attributes, but keep the (v2) public key.
+                let mut public_key = None;
+                while let Some(value) = reader.read_optional(|reader| reader.read_tagged_der())? {
+                    if value.tag() == Tag::context(1) {
+                        // The contents of a BIT STRING start with the number of unused bits.
+                        public_key = value.value().split_first().map(|(_, key)| key.to_vec());
+                    }
+                }
+                Ok(PrivateKeyInfo {
+                    oids,
+                    body,
+                    public_key,
+                })
+            })
+        })
+    }
+
+    /// Reads a `SubjectPublicKeyInfo`, returning its algorithm and the encoded public key.
+    pub fn read_public(der: &[u8]) -> ASN1Result<(AlgorithmOids, Vec<u8>)> {
+        yasna::parse_der(der, |reader| {
+            reader.read_sequence(|reader| {
+                let oids = reader.next().read_sequence(read_oids)?;
+                let (body, _num_bits) = reader.next().read_bitvec_bytes()?;
+                Ok((oids, body))
+            })
+        })
+    }
+}
+
+#[cfg(feature = "pkcs-convert")]
+pub mod x509 {
+    use yasna::{models::ObjectIdentifier, ASN1Result, Tag};
+
+    /// Writes the instant `unix_secs` as an X.509 `Time`: a `UTCTime` until 2049 and a
+    /// `GeneralizedTime` thereafter ([RFC 5280 §4.1.2.5](https://tools.ietf.org/html/rfc5280#section-4.1.2.5)).
+    #[cfg(feature = "crypto")]
+    pub fn write_time(writer: yasna::DERWriter, unix_secs: u64) {
+        // Converts days since 1970-01-01 to a (proleptic Gregorian) date, as per
+        // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let days = unix_secs / 86400 + 719_468;
+        let (era, day_of_era) = (days / 146_097, days % 146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + (month <= 2) as u64;
+
+        let secs = unix_secs % 86400;
+        let time = format!(
+            "{:02}{:02}{:02}{:02}{:02}Z",
+            month,
+            day,
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60
+        );
+        if year < 2050 {
+            let utc_time = format!("{:02}{}", year % 100, time);
+            writer.write_tagged_implicit(yasna::tags::TAG_UTCTIME, |writer| {
+                writer.write_bytes(utc_time.as_bytes())
+            });
+        } else {
+            let generalized_time = format!("{:04}{}", year, time);
+            writer.write_tagged_implicit(yasna::tags::TAG_GENERALIZEDTIME, |writer| {
+                writer.write_bytes(generalized_time.as_bytes())
+            });
+        }
+    }
+
+    /// The parts of a decoded X.509 `Certificate` needed to bind it to a key and to its issuer.
+    #[cfg_attr(not(feature = "crypto"), allow(dead_code))]
+    pub struct Certificate {
+        /// The encoded `TBSCertificate`, over which the signature is computed.
+        pub tbs: Vec<u8>,
+        pub signature_algorithm: ObjectIdentifier,
+        pub signature: Vec<u8>,
+        /// The encoded issuer `Name`.
+        pub issuer: Vec<u8>,
+        /// The encoded subject `Name`.
+        pub subject: Vec<u8>,
+        /// The encoded `SubjectPublicKeyInfo`.
+        pub public_key_info: Vec<u8>,
+    }
+
+    /// Reads a DER-encoded X.509 `Certificate`.
+    pub fn read_certificate(der: &[u8]) -> ASN1Result<Certificate> {
+        let (tbs, signature_algorithm, signature) = yasna::parse_der(der, |reader| {
+            reader.read_sequence(|reader| {
+                let tbs = reader.next().read_der()?;
+                let signature_algorithm = reader.next().read_sequence(|reader| {
+                    let oid = reader.next().read_oid()?;
+                    reader.read_optional(|reader| reader.read_der())?; // parameters
+                    Ok(oid)
+                })?;
+                let (signature, _num_bits) = reader.next().read_bitvec_bytes()?;
+                Ok((tbs, signature_algorithm, signature))
+            })
+        })?;
+        let (issuer, subject, public_key_info) = yasna::parse_der(&tbs, |reader| {
+            reader.read_sequence(|reader| {
+                reader.read_optional(|reader| {
+                    reader.read_tagged(Tag::context(0), |reader| reader.read_u8())
+                    // version
+                })?;
+                reader.next().read_der()?; // serialNumber
+                reader.next().read_der()?; // signature
+                let issuer = reader.next().read_der()?;
+                reader.next().read_der()?; // validity
+                let subject = reader.next().read_der()?;
+                let public_key_info = reader.next().read_der()?;
+                // Skip the unique identifiers and extensions.
+                while reader.read_optional(|reader| reader.read_der())?.is_some() {}
+                Ok((issuer, subject, public_key_info))
+            })
+        })?;
+        Ok(Certificate {
+            tbs,
+            signature_algorithm,
+            signature,
+            issuer,
+            subject,
+            public_key_info,
+        })
+    }
+
+    /// Reads a DER-encoded X.509 `Certificate`, returning its encoded `SubjectPublicKeyInfo`.
+    pub fn read_public_key_info(der: &[u8]) -> ASN1Result<Vec<u8>> {
+        read_certificate(der).map(|certificate| certificate.public_key_info)
+    }
+}
+
+#[cfg(feature = "pkcs-convert")]
+pub mod pem {
+    use zeroize::Zeroizing;
+
+    use crate::ConversionError;
+
+    /// The label (e.g., `PRIVATE KEY`) and decoded contents of a PEM block.
+    pub type Block = (String, Zeroizing<Vec<u8>>);
+
+    /// Returns the label and decoded contents of each PEM block in `pem`.
+    /// Text outside of blocks is ignored.
+    pub fn decode_blocks(pem: &str) -> Result<Vec<Block>, ConversionError> {
+        let mut blocks = Vec::new();
+        let mut lines = pem.lines().map(str::trim);
+        while let Some(line) = lines.next() {
+            let label = match line
+                .strip_prefix("-----BEGIN ")
+                .and_then(|l| l.strip_suffix("-----"))
+            {
+                Some(label) => label,
+                None => continue,
+            };
+            let end = format!("-----END {}-----", label);
+            let mut b64 = Zeroizing::new(String::new());
+            loop {
+                match lines.next() {
+                    Some(line) if line == end => break,
+                    Some(line) => b64.push_str(line),
+                    None => return Err(ConversionError::InvalidPem),
+                }
+            }
+            let der = base64::decode(&*b64).map_err(|_| ConversionError::InvalidPem)?;
+            blocks.push((label.to_string(), Zeroizing::new(der)));
+        }
+        Ok(blocks)
+    }
+
+    /// Encodes `der` as a PEM block having the provided label.
+    pub fn encode(label: &str, der: &[u8]) -> String {
+        encode_with(label, der, 64, "\n")
+    }
+
+    /// Like `encode`, but wrapping the base64 at `line_len` (or not at all, if zero) and ending
+    /// lines with `eol`.
+    pub fn encode_with(label: &str, der: &[u8], line_len: usize, eol: &str) -> String {
+        use std::fmt::Write;
+
+        let der_b64 = Zeroizing::new(base64::encode(der));
+        let line_len = if line_len == 0 {
+            der_b64.len().max(1)
+        } else {
+            line_len
+        };
+        // Allocate up front so that growing the string doesn't leave copies of the key behind.
+        let mut pem =
+            String::with_capacity(der_b64.len() + (der_b64.len() / line_len + 3) * eol.len() + 64);
+        write!(&mut pem, "-----BEGIN {}-----{}", label, eol).unwrap();
+        //^ re: `unwrap`, if writing to a string fails, we've got bigger issues.
+        for i in (0..der_b64.len()).step_by(line_len) {
+            write!(
+                &mut pem,
+                "{}{}",
+                &der_b64[i..std::cmp::min(i + line_len, der_b64.len())],
+                eol
+            )
+            .unwrap();
+        }
+        write!(&mut pem, "-----END {}-----{}", label, eol).unwrap();
+        pem
+    }
+
+    /// Returns the decoded contents of each `CERTIFICATE` block in `pem`.
+    pub fn decode_certificates(pem: &str) -> Result<Vec<Vec<u8>>, ConversionError> {
+        Ok(decode_blocks(pem)?
+            .into_iter()
+            .filter(|(label, _der)| label == "CERTIFICATE")
+            .map(|(_label, der)| der.to_vec())
+            .collect())
+    }
+}
+
+/// AES-CBC with PKCS#7 padding, and the AES key wrap mode, using the
+/// [aes](https://crates.io/crates/aes), [cbc](https://crates.io/crates/cbc), and
+/// [aes-kw](https://crates.io/crates/aes-kw) crates, as `ring` does not expose the AES block
+/// cipher.
+#[cfg(feature = "crypto")]
+pub mod aes {
+    use std::convert::TryFrom;
+
+    use ::aes::{Aes128, Aes192, Aes256};
+    use aes_kw::{KekAes128, KekAes192, KekAes256};
+    use cbc::cipher::{
+        block_padding::Pkcs7, BlockDecryptMut, BlockEncryptMut, InnerIvInit, KeyInit,
+    };
+    use zeroize::Zeroizing;
+
+    pub const BLOCK_LEN: usize = 16;
+
+    /// An expanded AES-128, AES-192, or AES-256 key, which is zeroized on drop.
+    #[derive(Clone)]
+    pub enum Aes {
+        Aes128(Aes128),
+        Aes192(Aes192),
+        Aes256(Aes256),
+    }
+
+    /// Evaluates `$body` with `$cipher` bound to the cipher of `$aes`, whatever its key size.
+    macro_rules! with_cipher {
+        ($aes:expr, $cipher:ident => $body:expr) => {
+            match $aes {
+                Aes::Aes128($cipher) => $body,
+                Aes::Aes192($cipher) => $body,
+                Aes::Aes256($cipher) => $body,
+            }
+        };
+    }
+
+    impl Aes {
+        /// Returns `None` if `key` is not 16, 24, or 32 bytes long.
+        pub fn new(key: &[u8]) -> Option<Self> {
+            Some(match key.len() {
+                16 => Self::Aes128(Aes128::new_from_slice(key).ok()?),
+                24 => Self::Aes192(Aes192::new_from_slice(key).ok()?),
+                32 => Self::Aes256(Aes256::new_from_slice(key).ok()?),
+                _ => return None,
+            })
+        }
+    }
+
+    /// Encrypts `plaintext` in CBC mode with PKCS#7 padding.
+    pub fn cbc_encrypt(aes: &Aes, iv: &[u8; BLOCK_LEN], plaintext: &[u8]) -> Vec<u8> {
+        with_cipher!(aes, cipher => {
+            cbc::Encryptor::inner_iv_init(cipher.clone(), iv.into())
+                .encrypt_padded_vec_mut::<Pkcs7>(plaintext)
+        })
+    }
+
+    /// Decrypts and unpads CBC-mode `ciphertext`, returning `None` if it is malformed.
+    pub fn cbc_decrypt(
+        aes: &Aes,
+        iv: &[u8; BLOCK_LEN],
+        ciphertext: &[u8],
+    ) -> Option<Zeroizing<Vec<u8>>> {
+        with_cipher!(aes, cipher => {
+            cbc::Decryptor::inner_iv_init(cipher.clone(), iv.into())
+                .decrypt_padded_vec_mut::<Pkcs7>(ciphertext)
+                .ok()
+                .map(Zeroizing::new)
+        })
+    }
+
+    /// Wraps `key`, whose length must be a multiple of 8 of at least 16, with the 16, 24, or
+    /// 32-byte key encryption key `kek` using the AES Key Wrap algorithm of
+    /// [RFC 3394](https://tools.ietf.org/html/rfc3394), as implemented by the
+    /// [aes-kw](https://crates.io/crates/aes-kw) crate.
+    pub fn key_wrap(kek: &[u8], key: &[u8]) -> Option<Vec<u8>> {
+        if key.len() < 16 {
+            return None;
+        }
+        let mut wrapped = vec![0; key.len() + 8];
+        match kek.len() {
+            16 => KekAes128::try_from(kek).ok()?.wrap(key, &mut wrapped),
+            24 => KekAes192::try_from(kek).ok()?.wrap(key, &mut wrapped),
+            32 => KekAes256::try_from(kek).ok()?.wrap(key, &mut wrapped),
+            _ => return None,
+        }
+        .ok()?;
+        Some(wrapped)
+    }
+
+    /// Unwraps a key wrapped by `key_wrap`, returning `None` if the integrity check fails.
+    pub fn key_unwrap(kek: &[u8], wrapped: &[u8]) -> Option<Zeroizing<Vec<u8>>> {
+        if wrapped.len() < 24 {
+            return None;
+        }
+        let mut key = Zeroizing::new(vec![0; wrapped.len() - 8]);
+        match kek.len() {
+            16 => KekAes128::try_from(kek).ok()?.unwrap(wrapped, &mut key),
+            24 => KekAes192::try_from(kek).ok()?.unwrap(wrapped, &mut key),
+            32 => KekAes256::try_from(kek).ok()?.unwrap(wrapped, &mut key),
+            _ => return None,
+        }
+        .ok()?;
+        Some(key)
+    }
+}
+
+/// Password-based encryption of PKCS#8 keys using PBES2 with PBKDF2 and AES-CBC
+/// ([RFC 8018 §6.2](https://tools.ietf.org/html/rfc8018#section-6.2)).
+#[cfg(feature = "crypto")]
+pub mod pbes2 {
+    use std::num::NonZeroU32;
+
+    use ring::pbkdf2;
+    use yasna::models::ObjectIdentifier;
+    use zeroize::Zeroizing;
+
+    use super::aes;
+    use crate::{jwe::PBES2_MAX_ITERATIONS, ConversionError};
+
+    const PBES2_OID: &[u64] = &[1, 2, 840, 113549, 1, 5, 13];
+    const PBKDF2_OID: &[u64] = &[1, 2, 840, 113549, 1, 5, 12];
+
+    /// The PBKDF2 pseudorandom functions, and their OIDs.
+    const PRF_OIDS: &[(&[u64], &pbkdf2::Algorithm)] = &[
+        (&[1, 2, 840, 113549, 2, 7], &pbkdf2::PBKDF2_HMAC_SHA1),
+        (&[1, 2, 840, 113549, 2, 9], &pbkdf2::PBKDF2_HMAC_SHA256),
+        (&[1, 2, 840, 113549, 2, 10], &pbkdf2::PBKDF2_HMAC_SHA384),
+        (&[1, 2, 840, 113549, 2, 11], &pbkdf2::PBKDF2_HMAC_SHA512),
+    ];
+
+    /// The AES-CBC encryption schemes, and their key lengths.
+    const CIPHER_OIDS: &[(&[u64], usize)] = &[
+        (&[2, 16, 840, 1, 101, 3, 4, 1, 2], 16),
+        (&[2, 16, 840, 1, 101, 3, 4, 1, 22], 24),
+        (&[2, 16, 840, 1, 101, 3, 4, 1, 42], 32),
+    ];
+
+    /// The parameters of PBKDF2 and of the AES-CBC encryption scheme.
+    struct Params {
+        salt: Vec<u8>,
+        iterations: u32,
+        key_len: Option<u32>,
+        prf: ObjectIdentifier,
+        cipher: ObjectIdentifier,
+        iv: Vec<u8>,
+    }
+
+    fn read_params(der: &[u8]) -> yasna::ASN1Result<Params> {
+        let hmac_with_sha1 = ObjectIdentifier::from_slice(PRF_OIDS[0].0);
+        yasna::parse_der(der, |reader| {
+            reader.read_sequence(|reader| {
+                let (kdf, salt, iterations, key_len, prf) =
+                    reader.next().read_sequence(|reader| {
+                        let kdf = reader.next().read_oid()?;
+                        let (salt, iterations, key_len, prf) =
+                            reader.next().read_sequence(|reader| {
+                                let salt = reader.next().read_bytes()?;
+                                let iterations = reader.next().read_u32()?;
+                                let key_len = reader.read_optional(|reader| reader.read_u32())?;
+                                let prf = reader.read_optional(|reader| {
+                                    reader.read_sequence(|reader| {
+                                        let prf = reader.next().read_oid()?;
+                                        reader.read_optional(|reader| reader.read_null())?;
+                                        Ok(prf)
+                                    })
+                                })?;
+                                Ok((salt, iterations, key_len, prf))
+                            })?;
+                        Ok((kdf, salt, iterations, key_len, prf))
+                    })?;
+                let (cipher, iv) = reader.next().read_sequence(|reader| {
+                    let cipher = reader.next().read_oid()?;
+                    Ok((cipher, reader.next().read_bytes()?))
+                })?;
+                if kdf != ObjectIdentifier::from_slice(PBKDF2_OID) {
+                    return Err(yasna::ASN1Error::new(yasna::ASN1ErrorKind::Invalid));
+                }
+                Ok(Params {
+                    salt,
+                    iterations,
+                    key_len,
+                    prf: prf.unwrap_or(hmac_with_sha1),
+                    cipher,
+                    iv,
+                })
+            })
+        })
+    }
+
+    /// Encrypts a DER-encoded `PrivateKeyInfo`, returning the encoded `EncryptedPrivateKeyInfo`.
+    pub fn encrypt(
+        der: &[u8],
+        password: &[u8],
+        params: &crate::Pbes2Params,
+    ) -> Result<Vec<u8>, ConversionError> {
+        use ring::rand::{SecureRandom, SystemRandom};
+
+        use crate::HashAlg;
+
+        let prf_oid: &[u64] = match params.prf {
+            HashAlg::Sha1 => PRF_OIDS[0].0,
+            HashAlg::Sha256 => PRF_OIDS[1].0,
+            HashAlg::Sha384 => PRF_OIDS[2].0,
+            HashAlg::Sha512 => PRF_OIDS[3].0,
+        };
+        let prf = PRF_OIDS
+            .iter()
+            .find(|(oid, _)| *oid == prf_oid)
+            .map(|(_, prf)| **prf)
+            .unwrap();
+        let cipher_oid = CIPHER_OIDS
+            .iter()
+            .find(|(_, key_len)| *key_len == params.key_len)
+            .map(|(oid, _)| ObjectIdentifier::from_slice(oid))
+            .ok_or(ConversionError::UnsupportedKey)?;
+        let iterations = NonZeroU32::new(params.iterations)
+            .filter(|iterations| iterations.get() <= PBES2_MAX_ITERATIONS)
+            .ok_or(ConversionError::UnsupportedKey)?;
+
+        let rng = SystemRandom::new();
+        let mut salt = [0; 16];
+        let mut iv = [0; aes::BLOCK_LEN];
+        rng.fill(&mut salt)
+            .and_then(|_| rng.fill(&mut iv))
+            .map_err(|_| ConversionError::UnsupportedKey)?;
+
+        let mut key = Zeroizing::new(vec![0; params.key_len]);
+        pbkdf2::derive(prf, iterations, &salt, password, &mut key);
+        let ciphertext = aes::cbc_encrypt(&aes::Aes::new(&key).unwrap(), &iv, der);
+
+        Ok(yasna::construct_der(|writer| {
+            writer.write_sequence(|writer| {
+                writer.next().write_sequence(|writer| {
+                    writer
+                        .next()
+                        .write_oid(&ObjectIdentifier::from_slice(PBES2_OID));
+                    writer.next().write_sequence(|writer| {
+                        writer.next().write_sequence(|writer| {
+                            writer
+                                .next()
+                                .write_oid(&ObjectIdentifier::from_slice(PBKDF2_OID));
+                            writer.next().write_sequence(|writer| {
+                                writer.next().write_bytes(&salt);
+                                writer.next().write_u32(params.iterations);
+                                // hmacWithSHA1 is the DEFAULT, so it must be omitted.
+                                if params.prf != HashAlg::Sha1 {
+                                    writer.next().write_sequence(|writer| {
+                                        writer
+                                            .next()
+                                            .write_oid(&ObjectIdentifier::from_slice(prf_oid));
+                                        writer.next().write_null();
+                                    });
+                                }
+                            });
+                        });
+                        writer.next().write_sequence(|writer| {
+                            writer.next().write_oid(&cipher_oid);
+                            writer.next().write_bytes(&iv);
+                        });
+                    });
+                });
+                writer.next().write_bytes(&ciphertext);
+            })
+        }))
+    }
+
+    /// Decrypts a DER-encoded `EncryptedPrivateKeyInfo`, returning the encoded `PrivateKeyInfo`.
+    pub fn decrypt(der: &[u8], password: &[u8]) -> Result<Zeroizing<Vec<u8>>, ConversionError> {
+        let (algorithm, params, ciphertext) = yasna::parse_der(der, |reader| {
+            reader.read_sequence(|reader| {
+                let (algorithm, params) = reader.next().read_sequence(|reader| {
+                    Ok((reader.next().read_oid()?, reader.next().read_der()?))
+                })?;
+                Ok((algorithm, params, reader.next().read_bytes()?))
+            })
+        })?;
+        if algorithm != ObjectIdentifier::from_slice(PBES2_OID) {
+            return Err(ConversionError::UnsupportedKey);
+        }
+        let params = read_params(&params).map_err(|_| ConversionError::UnsupportedKey)?;
+
+        let prf = PRF_OIDS
+            .iter()
+            .find(|(oid, _)| params.prf.components() == oid)
+            .map(|(_, prf)| **prf)
+            .ok_or(ConversionError::UnsupportedKey)?;
+        let key_len = CIPHER_OIDS
+            .iter()
+            .find(|(oid, _)| params.cipher.components() == oid)
+            .map(|(_, key_len)| *key_len)
+            .ok_or(ConversionError::UnsupportedKey)?;
+        let iterations = NonZeroU32::new(params.iterations)
+            .filter(|iterations| iterations.get() <= PBES2_MAX_ITERATIONS)
+            .ok_or(ConversionError::UnsupportedKey)?;
+        if params.key_len.is_some_and(|len| len as usize != key_len) {
+            return Err(ConversionError::UnsupportedKey);
+        }
+        let mut iv = [0; aes::BLOCK_LEN];
+        if params.iv.len() != iv.len() {
+            return Err(ConversionError::UnsupportedKey);
+        }
+        iv.copy_from_slice(&params.iv);
+
+        let mut key = Zeroizing::new(vec![0; key_len]);
+        pbkdf2::derive(prf, iterations, &params.salt, password, &mut key);
+        let aes = aes::Aes::new(&key).unwrap();
+        aes::cbc_decrypt(&aes, &iv, &ciphertext).ok_or(ConversionError::DecryptionFailed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn jcs() {
+        use super::jcs::to_string;
+
+        // https://tools.ietf.org/html/rfc8785#section-3.2.2, without 333333333.33333329, which
+        // serde_json doesn't parse to the nearest double by default (see Appendix B below).
+        let value: serde_json::Value = serde_json::from_str(
+            r#"{
+                "numbers": [1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+                "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+                "literals": [null, true, false]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            to_string(&value),
+            r#"{"literals":[null,true,false],"numbers":[1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#
+        );
+
+        // https://tools.ietf.org/html/rfc8785#section-3.2.3 (sorting by UTF-16 code units)
+        let value: serde_json::Value = serde_json::from_str(
+            r#"{"\u20ac": 0, "\r": 0, "\ufb33": 0, "1": 0, "\ud83d\ude00": 0, "\u0080": 0, "\u00f6": 0}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            to_string(&value),
+            "{\"\\r\":0,\"1\":0,\"\u{80}\":0,\"ö\":0,\"€\":0,\"😀\":0,\"\u{fb33}\":0}"
+        );
+
+        // https://tools.ietf.org/html/rfc8785#appendix-B
+        for (bits, expected) in &[
+            (0x0000000000000000_u64, "0"),
+            (0x8000000000000000, "0"),
+            (0x0000000000000001, "5e-324"),
+            (0x8000000000000001, "-5e-324"),
+            (0x7fefffffffffffff, "1.7976931348623157e+308"),
+            (0xffefffffffffffff, "-1.7976931348623157e+308"),
+            (0x4340000000000000, "9007199254740992"),
+            (0xc340000000000000, "-9007199254740992"),
+            (0x4430000000000000, "295147905179352830000"),
+            (0x44b52d02c7e14af5, "9.999999999999997e+22"),
+            (0x44b52d02c7e14af6, "1e+23"),
+            (0x44b52d02c7e14af7, "1.0000000000000001e+23"),
+            (0x444b1ae4d6e2ef4e, "999999999999999700000"),
+            (0x444b1ae4d6e2ef4f, "999999999999999900000"),
+            (0x444b1ae4d6e2ef50, "1e+21"),
+            (0x3eb0c6f7a0b5ed8c, "9.999999999999997e-7"),
+            (0x3eb0c6f7a0b5ed8d, "0.000001"),
+            (0x41b3de4355555553, "333333333.3333332"),
+            (0x41b3de4355555554, "333333333.33333325"),
+            (0x41b3de4355555555, "333333333.3333333"),
+            (0x41b3de4355555556, "333333333.3333334"),
+            (0x41b3de4355555557, "333333333.33333343"),
+            (0xbecbf647612f3696, "-0.0000033333333333333333"),
+            (0x43143ff3c1cb0959, "1424953923781206.2"),
+        ] {
+            let n = serde_json::Number::from_f64(f64::from_bits(*bits)).unwrap();
+            assert_eq!(to_string(&serde_json::Value::Number(n)), *expected);
+        }
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn curve25519() {
+        use super::curve25519::{ed25519_public, x25519, x25519_public};
+
+        let hex = |s: &str| {
+            let mut bytes = [0; 32];
+            for (i, byte) in bytes.iter_mut().enumerate() {
+                *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+            }
+            bytes
+        };
+        // https://tools.ietf.org/html/rfc7748#section-5.2
+        assert_eq!(
+            x25519(
+                &hex("a546e36bf0527c9d3b16154b82465edd62144c0ac1fc5a18506a2244ba449ac4"),
+                &hex("e6db6867583030db3594c1a424b15f7c726624ec26b3353b10a903a6d0ab1c4c")
+            ),
+            hex("c3da55379de9c6908e94ea4df28d084f32eccf03491c71f754b4075577a28552")
+        );
+        // https://tools.ietf.org/html/rfc7748#section-6.1
+        let alice = hex("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a");
+        let bob = hex("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb");
+        assert_eq!(
+            x25519_public(&alice),
+            hex("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a")
+        );
+        assert_eq!(
+            x25519_public(&bob),
+            hex("de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f")
+        );
+        assert_eq!(
+            x25519(&alice, &x25519_public(&bob)),
+            hex("4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742")
+        );
+
+        // https://tools.ietf.org/html/rfc8032#section-7.1
+        for (seed, public) in &[
+            (
+                "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+                "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+            ),
+            (
+                "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+                "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+            ),
+        ] {
+            assert_eq!(ed25519_public(&hex(seed)), hex(public));
+        }
+    }
+
+    #[cfg(feature = "crypto")]
+    #[test]
+    fn write_time() {
+        let time =
+            |unix_secs| yasna::construct_der(|writer| super::x509::write_time(writer, unix_secs));
+        assert_eq!(time(0), b"\x17\x0d700101000000Z");
+        assert_eq!(time(951_782_400), b"\x17\x0d000229000000Z");
+        assert_eq!(time(1_792_078_923), b"\x17\x0d261015154203Z");
+        assert_eq!(time(2_524_608_000), b"\x18\x0f20500101000000Z");
+        assert_eq!(time(4_107_542_399), b"\x18\x0f21000228235959Z");
+    }
+
+    #[cfg(feature = "did-key")]
+    #[test]
+    fn base58() {
+        use super::base58::{decode, encode};
+
+        // https://tools.ietf.org/html/draft-msporny-base58-03#section-5
+        assert_eq!(encode(b"Hello World!"), "2NEpo7TZRRrLZSi2U");
+        assert_eq!(encode(&[0, 0, 0x28, 0x7f, 0xb4, 0xcd]), "11233QC4");
+        assert_eq!(encode(&[]), "");
+        assert_eq!(decode("2NEpo7TZRRrLZSi2U").unwrap(), b"Hello World!");
+        assert_eq!(decode("11233QC4").unwrap(), [0, 0, 0x28, 0x7f, 0xb4, 0xcd]);
+        assert_eq!(decode("").unwrap(), b"");
+        assert!(decode("0OIl").is_none());
+    }
+
+    #[cfg(feature = "crypto")]
+    #[test]
+    fn aes() {
+        use super::aes::{cbc_decrypt, cbc_encrypt, Aes};
+
+        let hex = |s: &str| {
+            (0..s.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+                .collect::<Vec<u8>>()
+        };
+        // FIPS 197, Appendix C
+        let key = hex("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f");
+        for (key_len, ciphertext) in &[
+            (16, "69c4e0d86a7b0430d8cdb78070b4c55a"),
+            (24, "dda97ca4864cdfe06eaf70a0ec0d7191"),
+            (32, "8ea2b7ca516745bfeafc49904b496089"),
+        ] {
+            // With a zero IV, the first CBC block is the block cipher output.
+            let aes = Aes::new(&key[..*key_len]).unwrap();
+            let plaintext = hex("00112233445566778899aabbccddeeff");
+            let encrypted = cbc_encrypt(&aes, &[0; 16], &plaintext);
+            assert_eq!(encrypted[..16], hex(ciphertext)[..]);
+            assert_eq!(*cbc_decrypt(&aes, &[0; 16], &encrypted).unwrap(), plaintext);
+        }
+        assert!(Aes::new(&key[..15]).is_none());
+
+        let aes = Aes::new(&key[..16]).unwrap();
+        let iv = [7; 16];
+        for len in &[0, 1, 15, 16, 17, 32] {
+            let plaintext = vec![0xa5; *len];
+            let ciphertext = cbc_encrypt(&aes, &iv, &plaintext);
+            assert_eq!(ciphertext.len(), (len / 16 + 1) * 16);
+            assert_eq!(*cbc_decrypt(&aes, &iv, &ciphertext).unwrap(), plaintext);
+        }
+        let mut ciphertext = cbc_encrypt(&aes, &iv, b"jsonwebkey");
+        *ciphertext.last_mut().unwrap() ^= 1;
+        assert!(cbc_decrypt(&aes, &iv, &ciphertext).is_none());
+        assert!(cbc_decrypt(&aes, &iv, &ciphertext[..15]).is_none());
+        assert!(cbc_decrypt(&aes, &iv, &[]).is_none());
+    }
+
+    #[cfg(feature = "crypto")]
+    #[test]
+    fn aes_key_wrap() {
+        use super::aes::{key_unwrap, key_wrap};
+
+        let hex = |s: &str| {
+            (0..s.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+                .collect::<Vec<u8>>()
+        };
+        // RFC 3394 §4.1, 4.3, and 4.6
+        let kek = hex("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f");
+        let key = hex("00112233445566778899aabbccddeeff000102030405060708090a0b0c0d0e0f");
+        for (kek_len, key_len, wrapped) in &[
+            (16, 16, "1fa68b0a8112b447aef34bd8fb5a7b829d3e862371d2cfe5"),
+            (32, 16, "64e8c3f9ce0f5ba263e9777905818a2a93c8191e7d6e8ae7"),
+            (
+                32,
+                32,
+                "28c9f404c4b810f4cbccb35cfb87f8263f5786e2d80ed326cbc7f0e71a99f43bfb988b9b7a02dd21",
+            ),
+        ] {
+            let kek = &kek[..*kek_len];
+            assert_eq!(key_wrap(kek, &key[..*key_len]).unwrap(), hex(wrapped));
+            assert_eq!(*key_unwrap(kek, &hex(wrapped)).unwrap(), &key[..*key_len]);
+        }
+
+        let kek = &kek[..16];
+        let mut wrapped = key_wrap(kek, &key).unwrap();
+        wrapped[0] ^= 1;
+        assert!(key_unwrap(kek, &wrapped).is_none());
+        assert!(key_unwrap(kek, &wrapped[..16]).is_none());
+        assert!(key_wrap(kek, &key[..8]).is_none());
+        assert!(key_wrap(kek, &key[..20]).is_none());
+        assert!(key_wrap(&kek[..15], &key).is_none());
+    }
 }